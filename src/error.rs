@@ -1,18 +1,24 @@
+use serde_json::error::Category;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum NanoDBError {
-    // Serde serialize error
+    // Serde serialize error, stored as its message and category so that the error stays `Clone`
     #[error("Serde deserialize error: {0}")]
-    DeserializeFromStr(#[from] serde_json::Error),
-    // IO error
+    DeserializeFromStr(String, Category),
+    // Error of a non-JSON backing format
+    #[error("Format error: {0}")]
+    Format(String),
+    // IO error, stored as its message and kind so that the error stays `Clone`
     #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(String, std::io::ErrorKind),
     // Lock error
     #[error("RwLock Read error: {0}")]
     RwLockReadError(String),
     #[error("RwLock Write error: {0}")]
     RwLockWriteError(String),
+    #[error("Timed out waiting for the lock")]
+    LockTimeout,
     #[error("The value at '{0}' is not array")]
     NotAnArray(String),
     #[error("The value at '{0}' does not have a length property")]
@@ -21,13 +27,192 @@ pub enum NanoDBError {
     NotAnObject(String),
     #[error("Key '{0}' not found")]
     KeyNotFound(String),
+    #[error("Key '{missing}' not found at '{path}'")]
+    PathNotFound { path: String, missing: String },
+    #[error("Element not found in the array at '{0}'")]
+    ElementNotFound(String),
+    #[error("The array at '{0}' cannot be sorted")]
+    Unsortable(String),
     #[error("Index {0} is out of bounds")]
     IndexOutOfBounds(usize),
     #[error("Invalid JSON path")]
     InvalidJSONPath,
-    #[error("Type mismatch: {0}")]
-    TypeMismatch(String),
+    #[error("The database has no backing file")]
+    NoBackingFile,
+    #[error("Invalid JSON schema: {0}")]
+    InvalidSchema(String),
+    #[error("Schema violation: {}", .0.join("; "))]
+    SchemaViolation(Vec<String>),
+    #[error("Type mismatch: expected {expected}, found {actual}: {detail}")]
+    TypeMismatch {
+        expected: String,
+        actual: String,
+        detail: String,
+    },
+    #[error("The serialized data is {size} bytes, which exceeds the limit of {limit} bytes")]
+    SizeLimitExceeded { size: usize, limit: usize },
+    #[error("Patch operation '{op}' failed at '{path}'")]
+    PatchFailed { op: String, path: String },
+    #[error("Replaying line {line} of the journal failed: {source}")]
+    JournalReplayFailed {
+        line: usize,
+        source: Box<NanoDBError>,
+    },
     // Default error
     #[error("An error occurred")]
     DefaultError,
 }
+
+impl NanoDBError {
+    /// Returns `true` if the error is caused by a missing key, index or path.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            NanoDBError::KeyNotFound(_)
+                | NanoDBError::IndexOutOfBounds(_)
+                | NanoDBError::PathNotFound { .. }
+        )
+    }
+
+    /// Returns `true` if the error is caused by a value not having the expected type.
+    pub fn is_type_error(&self) -> bool {
+        matches!(
+            self,
+            NanoDBError::NotAnArray(_)
+                | NanoDBError::NotAnObject(_)
+                | NanoDBError::TypeMismatch { .. }
+        )
+    }
+
+    /// Returns `true` if the error is caused by acquiring a lock.
+    pub fn is_lock_error(&self) -> bool {
+        matches!(
+            self,
+            NanoDBError::RwLockReadError(_)
+                | NanoDBError::RwLockWriteError(_)
+                | NanoDBError::LockTimeout
+        )
+    }
+}
+
+impl From<serde_json::Error> for NanoDBError {
+    fn from(e: serde_json::Error) -> Self {
+        NanoDBError::DeserializeFromStr(e.to_string(), e.classify())
+    }
+}
+
+impl From<std::io::Error> for NanoDBError {
+    fn from(e: std::io::Error) -> Self {
+        NanoDBError::Io(e.to_string(), e.kind())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NanoDBError;
+    use serde_json::error::Category;
+
+    #[test]
+    fn test_clone() {
+        let serde_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing file");
+        let errors = vec![
+            NanoDBError::from(serde_error),
+            NanoDBError::from(io_error),
+            NanoDBError::Format("format".to_string()),
+            NanoDBError::RwLockReadError("read".to_string()),
+            NanoDBError::RwLockWriteError("write".to_string()),
+            NanoDBError::LockTimeout,
+            NanoDBError::NotAnArray("a".to_string()),
+            NanoDBError::LenNotDefined("a".to_string()),
+            NanoDBError::NotAnObject("a".to_string()),
+            NanoDBError::KeyNotFound("a".to_string()),
+            NanoDBError::PathNotFound {
+                path: "a".to_string(),
+                missing: "b".to_string(),
+            },
+            NanoDBError::ElementNotFound("a".to_string()),
+            NanoDBError::Unsortable("a".to_string()),
+            NanoDBError::IndexOutOfBounds(1),
+            NanoDBError::InvalidJSONPath,
+            NanoDBError::NoBackingFile,
+            NanoDBError::InvalidSchema("a".to_string()),
+            NanoDBError::SchemaViolation(vec!["a".to_string(), "b".to_string()]),
+            NanoDBError::TypeMismatch {
+                expected: "a".to_string(),
+                actual: "b".to_string(),
+                detail: "c".to_string(),
+            },
+            NanoDBError::SizeLimitExceeded { size: 2, limit: 1 },
+            NanoDBError::PatchFailed {
+                op: "a".to_string(),
+                path: "b".to_string(),
+            },
+            NanoDBError::JournalReplayFailed {
+                line: 1,
+                source: Box::new(NanoDBError::InvalidJSONPath),
+            },
+            NanoDBError::DefaultError,
+        ];
+        for error in errors {
+            let cloned = error.clone();
+            assert_eq!(cloned.to_string(), error.to_string());
+            assert_eq!(format!("{:?}", cloned), format!("{:?}", error));
+        }
+    }
+
+    #[test]
+    fn test_source_errors_keep_display_and_kind() {
+        let serde_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let message = format!("Serde deserialize error: {}", serde_error);
+        let error = NanoDBError::from(serde_error);
+        assert_eq!(error.to_string(), message);
+        assert!(matches!(
+            error,
+            NanoDBError::DeserializeFromStr(_, Category::Eof)
+        ));
+
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing file");
+        let error = NanoDBError::from(io_error);
+        assert_eq!(error.to_string(), "IO error: missing file");
+        assert!(matches!(
+            error,
+            NanoDBError::Io(_, std::io::ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_is_not_found() {
+        assert!(NanoDBError::KeyNotFound("a".to_string()).is_not_found());
+        assert!(NanoDBError::IndexOutOfBounds(1).is_not_found());
+        assert!(NanoDBError::PathNotFound {
+            path: "a".to_string(),
+            missing: "b".to_string()
+        }
+        .is_not_found());
+        assert!(!NanoDBError::NotAnObject("a".to_string()).is_not_found());
+        assert!(!NanoDBError::InvalidJSONPath.is_not_found());
+    }
+
+    #[test]
+    fn test_is_type_error() {
+        assert!(NanoDBError::NotAnArray("a".to_string()).is_type_error());
+        assert!(NanoDBError::NotAnObject("a".to_string()).is_type_error());
+        assert!(NanoDBError::TypeMismatch {
+            expected: "a".to_string(),
+            actual: "b".to_string(),
+            detail: "c".to_string(),
+        }
+        .is_type_error());
+        assert!(!NanoDBError::KeyNotFound("a".to_string()).is_type_error());
+        assert!(!NanoDBError::LenNotDefined("a".to_string()).is_type_error());
+    }
+
+    #[test]
+    fn test_is_lock_error() {
+        assert!(NanoDBError::RwLockReadError("a".to_string()).is_lock_error());
+        assert!(NanoDBError::RwLockWriteError("a".to_string()).is_lock_error());
+        assert!(NanoDBError::LockTimeout.is_lock_error());
+        assert!(!NanoDBError::NoBackingFile.is_lock_error());
+    }
+}
@@ -32,15 +32,15 @@
 //! #[tokio::main]
 //! async fn main() -> Result<(), NanoDBError> {
 //!     let json_data = r#"{
-//! 			"key1": "Welcome!",
-//! 			"key2": 42,
-//! 			"key3": {
-//! 				"name": "NanoDB",
-//! 				"versions": [1.0, 2.0, 3.0]
-//! 			},
-//! 			"key4": [1, 2, 3],
-//! 			"key5": ["Welcome", "to", "NanoDB"]
-//! 		}"#;
+//!             "key1": "Welcome!",
+//!             "key2": 42,
+//!             "key3": {
+//!                 "name": "NanoDB",
+//!                 "versions": [1.0, 2.0, 3.0]
+//!             },
+//!             "key4": [1, 2, 3],
+//!             "key5": ["Welcome", "to", "NanoDB"]
+//!         }"#;
 //!
 //!     let mut db = NanoDB::new_from("examples/data/data2.json", json_data).unwrap();
 //!
@@ -76,7 +76,7 @@
 //!     db.insert_tree(my_tree).await?;
 //!     db.write().await?;
 //!
-//! 	// Advanced write locks
+//!     // Advanced write locks
 //!     let mut write_lock = db.update().await;
 //!     write_lock.insert("key1", "Welcome to NanoDB")?;
 //!     write_lock.insert("key1", "Welcome to NanoDB again")?;
@@ -1,20 +1,139 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{path::PathBuf, sync::Arc};
-use tempfile::tempdir;
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
     error::NanoDBError,
-    trees::{tree::Tree, tree_read_guarded::ReadGuardedTree, tree_write_guarded::WriteGuardedTree},
+    trees::{
+        tree::{ArrayMerge, PathStep, Tree, TreeType},
+        tree_helper,
+        tree_read_guarded::ReadGuardedTree,
+        tree_write_guarded::WriteGuardedTree,
+    },
 };
 
+/// The format used to serialize the JSON data when it is written to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteFormat {
+    /// Human-readable JSON, indented by `indent` spaces per level.
+    Pretty { indent: usize },
+    /// JSON without any whitespace.
+    Compact,
+}
+
+impl Default for WriteFormat {
+    fn default() -> Self {
+        WriteFormat::Pretty { indent: 2 }
+    }
+}
+
+impl WriteFormat {
+    fn serialize(&self, data: &Value) -> Result<Vec<u8>, NanoDBError> {
+        match self {
+            WriteFormat::Pretty { indent } => {
+                let indent = b" ".repeat(*indent);
+                let formatter = serde_json::ser::PrettyFormatter::with_indent(&indent);
+                let mut buf = Vec::new();
+                let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+                data.serialize(&mut ser)?;
+                Ok(buf)
+            }
+            WriteFormat::Compact => Ok(serde_json::to_vec(data)?),
+        }
+    }
+}
+
+/// The format of the file backing a NanoDB instance.
+///
+/// The in-memory representation is always a `serde_json::Value`; the format only determines how the
+/// data is decoded in `open` and encoded in `write` and `backup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// JSON, serialized according to the configured `WriteFormat`.
+    #[default]
+    Json,
+    /// YAML. Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
+    /// MessagePack, a compact binary format. Requires the `msgpack` feature.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl Format {
+    /// Infers the format from the extension of `path`, falling back to `Format::Json`.
+    ///
+    /// A trailing `.gz` extension is ignored, so `file.yaml.gz` is inferred as YAML.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the backing file.
+    ///
+    /// # Returns
+    ///
+    /// * `Format` - `Format::Yaml` for `.yaml`/`.yml` files (with the `yaml` feature), `Format::Json` otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        let path = match (path.extension(), path.file_stem()) {
+            (Some(ext), Some(stem)) if ext == "gz" => Path::new(stem),
+            _ => path,
+        };
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension {
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => Format::Yaml,
+            _ => Format::Json,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, NanoDBError> {
+        match self {
+            Format::Json => Ok(serde_json::from_slice(bytes)?),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => {
+                serde_yaml::from_slice(bytes).map_err(|e| NanoDBError::Format(e.to_string()))
+            }
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| NanoDBError::Format(e.to_string()))
+            }
+        }
+    }
+
+    fn encode(&self, data: &Value, write_format: WriteFormat) -> Result<Vec<u8>, NanoDBError> {
+        match self {
+            Format::Json => write_format.serialize(data),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(data)
+                .map(String::into_bytes)
+                .map_err(|e| NanoDBError::Format(e.to_string())),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => {
+                rmp_serde::to_vec(data).map_err(|e| NanoDBError::Format(e.to_string()))
+            }
+        }
+    }
+}
+
 /// A struct representing a NanoDB instance.
 ///
 /// # Fields
 ///
-/// * `path` - The path to the JSON file that this NanoDB instance is managing.
+/// * `path` - The path to the JSON file that this NanoDB instance is managing, or `None` for an in-memory instance.
 /// * `data` - The JSON data that this NanoDB instance is managing.
+/// * `dirty` - Whether the JSON data has been modified since it was last read from or written to disk.
+/// * `format` - The format of the backing file.
+/// * `write_format` - The format used by `write` and `backup` to serialize the JSON data.
+/// * `compressed` - Whether the backing file is gzip-compressed. Requires the `gzip` feature.
+/// * `schema` - The JSON schema checked by `insert_validated`, if any. Requires the `schema` feature.
 ///
 /// # Methods
 ///
@@ -26,13 +145,27 @@ use crate::{
 /// * `merge` - Pushes a value to a nested array specified by a string path.
 #[derive(Debug)]
 pub struct NanoDB {
-    path: PathBuf,
+    path: Option<PathBuf>,
     data: Arc<RwLock<Value>>,
+    dirty: Arc<AtomicBool>,
+    format: Format,
+    write_format: WriteFormat,
+    max_bytes: Option<usize>,
+    snapshot: Arc<std::sync::Mutex<Option<Arc<Value>>>>,
+    journal: Option<PathBuf>,
+    pending_journal: Arc<std::sync::Mutex<Vec<JournalOp>>>,
+    temp_dir: Option<Arc<tempfile::TempDir>>,
+    #[cfg(feature = "gzip")]
+    compressed: bool,
+    #[cfg(feature = "schema")]
+    schema: Option<Arc<jsonschema::Validator>>,
 }
 impl NanoDB {
     /// Creates a new NanoDB instance with the JSON data from the file at the given path.
     ///
     /// If the file does not exist, the NanoDB instance is initialized with an empty JSON object.
+    /// The format of the file is inferred from its extension, see `Format::from_path`. With the `gzip`
+    /// feature, files ending in `.gz` are opened as compressed files, see `open_compressed`.
     ///
     /// # Arguments
     ///
@@ -51,17 +184,84 @@ impl NanoDB {
     /// ```
     pub fn open(path: impl Into<PathBuf>) -> Result<Self, NanoDBError> {
         let path = path.into();
-        let data = if path.exists() {
-            let contents = std::fs::read_to_string(&path)?;
-            serde_json::from_str(&contents)?
-        } else {
-            Value::Object(Default::default())
-        };
+        #[cfg(feature = "gzip")]
+        if path.extension().is_some_and(|ext| ext == "gz") {
+            return Self::open_compressed(path);
+        }
+        let format = Format::from_path(&path);
+        Self::open_with_format(path, format)
+    }
 
-        Ok(Self {
-            path,
-            data: Arc::new(RwLock::new(data)),
-        })
+    /// Creates a new NanoDB instance with the data from the gzip-compressed file at the given path.
+    ///
+    /// If the file does not exist, the NanoDB instance is initialized with an empty JSON object.
+    /// The format of the decompressed data is inferred from the extension without `.gz`, see
+    /// `Format::from_path`. Subsequent writes are compressed as well. Requires the `gzip` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the compressed file. This argument is converted into a `PathBuf`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NanoDB)` - A new NanoDB instance with the data from the file at `path`.
+    /// * `Err(std::io::Error)` - If there was an error reading or decompressing the file.
+    /// * `Err(serde_json::Error)` - If there was an error parsing the decompressed contents as JSON.
+    #[cfg(feature = "gzip")]
+    pub fn open_compressed(path: impl Into<PathBuf>) -> Result<Self, NanoDBError> {
+        let path = path.into();
+        let mut db = Self::with_data(None, Value::Null);
+        db.format = Format::from_path(&path);
+        db.compressed = true;
+        db.path = Some(path);
+        db._load()?;
+        Ok(db)
+    }
+
+    /// Creates a new NanoDB instance with the data from the file at the given path, decoded using `format`.
+    ///
+    /// If the file does not exist, the NanoDB instance is initialized with an empty JSON object.
+    /// Subsequent writes use the same format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file. This argument is converted into a `PathBuf`.
+    /// * `format` - The format of the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NanoDB)` - A new NanoDB instance with the data from the file at `path`.
+    /// * `Err(std::io::Error)` - If there was an error reading the file.
+    /// * `Err(serde_json::Error)` - If there was an error parsing the file contents as JSON.
+    /// * `Err(NanoDBError::Format)` - If there was an error parsing the file contents in another format.
+    pub fn open_with_format(path: impl Into<PathBuf>, format: Format) -> Result<Self, NanoDBError> {
+        let mut db = Self::with_data(Some(path.into()), Value::Null);
+        db.format = format;
+        db._load()?;
+        Ok(db)
+    }
+
+    /// Opens the JSON file at the given path, creating it with an empty JSON object if it does not exist.
+    ///
+    /// Unlike `open`, this guarantees that the file exists on disk once the NanoDB instance is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the JSON file. This argument is converted into a `PathBuf`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NanoDB)` - A new NanoDB instance with the JSON data from the file at `path`.
+    /// * `Err(std::io::Error)` - If there was an error reading or creating the file.
+    /// * `Err(serde_json::Error)` - If there was an error parsing the file contents as JSON.
+    pub fn open_or_create(path: impl Into<PathBuf>) -> Result<Self, NanoDBError> {
+        let path = path.into();
+        let exists = path.exists();
+        let db = Self::open(&path)?;
+        if !exists {
+            std::fs::write(&path, db._encode(&Value::Object(Default::default()))?)?;
+        }
+        Ok(db)
     }
 
     /// Creates a new NanoDB instance with the given JSON data and writes it to the file at the given path.
@@ -80,18 +280,109 @@ impl NanoDB {
     /// * `Err(serde_json::Error)` - If there was an error parsing `contents` as JSON.
     pub fn new_from(path: impl Into<PathBuf>, contents: &str) -> Result<Self, NanoDBError> {
         let data = serde_json::from_str(contents)?;
-        let _path: PathBuf;
-        if cfg!(test) {
-            let tmp_dir = tempdir()?;
-            _path = tmp_dir.path().join("my_file.json");
-        } else {
-            _path = path.into();
-            std::fs::write(&_path, contents)?;
-        }
-        Ok(Self {
-            path: _path,
+        let path = path.into();
+        std::fs::write(&path, contents)?;
+        Ok(Self::with_data(Some(path), data))
+    }
+
+    /// Creates a new NanoDB instance with the given JSON data, backed by a new file in the system's temporary directory.
+    ///
+    /// The file lives in its own temporary directory, which is removed once the instance and all of its clones
+    /// are dropped. This is mostly useful for tests and scratch data.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The JSON data to initialize the NanoDB instance with and write to the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NanoDB)` - A new NanoDB instance with the given JSON data.
+    /// * `Err(NanoDBError::Io)` - If the temporary file could not be created or written.
+    /// * `Err(serde_json::Error)` - If there was an error parsing `contents` as JSON.
+    pub fn new_from_temp(contents: &str) -> Result<Self, NanoDBError> {
+        let dir = tempfile::Builder::new().prefix("nanodb-").tempdir()?;
+        let mut db = Self::new_from(dir.path().join("data.json"), contents)?;
+        db.temp_dir = Some(Arc::new(dir));
+        Ok(db)
+    }
+
+    /// Creates a new NanoDB instance bound to the given path that wraps the given JSON value.
+    ///
+    /// Unlike `new_from`, the value is used as is instead of being parsed from a string, and nothing is
+    /// written to disk until `write` is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the JSON file. This argument is converted into a `PathBuf`.
+    /// * `value` - The JSON data to initialize the NanoDB instance with, e.g. built with `json!`.
+    ///
+    /// # Returns
+    ///
+    /// * `NanoDB` - A new NanoDB instance with the given JSON data.
+    pub fn from_value(path: impl Into<PathBuf>, value: Value) -> Self {
+        Self::with_data(Some(path.into()), value)
+    }
+
+    /// Creates a new NanoDB instance that only lives in memory and is not backed by any file.
+    ///
+    /// Reading and modifying the data works as usual, but `write` and `write_if_dirty` return
+    /// `NanoDBError::NoBackingFile` instead of touching the filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial` - The JSON data to initialize the NanoDB instance with.
+    ///
+    /// # Returns
+    ///
+    /// * `NanoDB` - A new in-memory NanoDB instance with the given JSON data.
+    pub fn new_in_memory(initial: Value) -> Self {
+        Self::with_data(None, initial)
+    }
+
+    /// Creates a new in-memory NanoDB instance with the JSON data read from the given reader.
+    ///
+    /// The instance has no backing file, see `new_in_memory`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read the JSON data from, e.g. stdin or a network stream.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NanoDB)` - A new in-memory NanoDB instance with the JSON data from `reader`.
+    /// * `Err(serde_json::Error)` - If there was an error reading or parsing the JSON data.
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, NanoDBError> {
+        let data = serde_json::from_reader(reader)?;
+        Ok(Self::with_data(None, data))
+    }
+
+    fn with_data(path: Option<PathBuf>, data: Value) -> Self {
+        Self {
+            path,
             data: Arc::new(RwLock::new(data)),
-        })
+            dirty: Arc::new(AtomicBool::new(false)),
+            format: Format::default(),
+            write_format: WriteFormat::default(),
+            max_bytes: None,
+            snapshot: Default::default(),
+            journal: None,
+            pending_journal: Default::default(),
+            temp_dir: None,
+            #[cfg(feature = "gzip")]
+            compressed: false,
+            #[cfg(feature = "schema")]
+            schema: None,
+        }
+    }
+
+    /// Returns the path of the file backing the NanoDB instance.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Path)` - The path of the backing file.
+    /// * `None` - If the NanoDB instance only lives in memory, see `new_in_memory`.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
     }
 
     /// Retrieves the value associated with a given key in the JSON data of the NanoDB instance.
@@ -110,6 +401,170 @@ impl NanoDB {
         Tree::new(data.clone(), vec![])
     }
 
+    /// Returns an immutable snapshot of the JSON data of the NanoDB instance.
+    ///
+    /// The snapshot is cached and shared between callers until the write lock is taken again (e.g. by a
+    /// mutation), so repeated calls on an unchanged document only clone the data once. Unlike `data()`,
+    /// this makes occasional whole-document reads cheap for read-heavy workloads.
+    ///
+    /// # Returns
+    ///
+    /// * `Arc<Value>` - A shared snapshot of the JSON data.
+    pub async fn snapshot(&self) -> Arc<Value> {
+        // the read lock keeps writers from invalidating the cache while it is being filled
+        let data = self._read_lock().await;
+        self._snapshot_cache()
+            .get_or_insert_with(|| Arc::new(data.clone()))
+            .clone()
+    }
+
+    /// Runs a closure against a borrow of the JSON data of the NanoDB instance and returns its result.
+    ///
+    /// Unlike `data()`, the document is not cloned, which makes this suitable for computing over large files.
+    /// The read lock is held for the duration of the closure.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure to run. It receives a reference to the live JSON data.
+    ///
+    /// # Returns
+    ///
+    /// * `R` - The value returned by the closure.
+    pub async fn read_with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Value) -> R,
+    {
+        let data = self._read_lock().await;
+        f(&data)
+    }
+
+    /// Retrieves the value associated with a top-level key in the JSON data of the NanoDB instance, if present.
+    ///
+    /// Only the value associated with `key` is cloned, not the whole document.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Tree))` - A new Tree object that represents the value associated with `key`.
+    /// * `Ok(None)` - If `key` does not exist in the JSON data.
+    /// * `Err(NanoDBError::NotAnObject)` - If the JSON data is not an object.
+    pub async fn get_optional(&self, key: &str) -> Result<Option<Tree>, NanoDBError> {
+        let data = self._read_lock().await;
+        let map = data
+            .as_object()
+            .ok_or_else(|| NanoDBError::NotAnObject(key.to_string()))?;
+        Ok(map
+            .get(key)
+            .map(|value| Tree::new(value.clone(), vec![PathStep::Key(key.to_string())])))
+    }
+
+    /// Retrieves the value associated with a given key in the JSON data of the NanoDB instance and converts it into
+    /// a specified type.
+    ///
+    /// Unlike `data().await.get(key)?.into()`, only the value at `key` is deserialized and nothing is cloned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - The value associated with `key`, converted into the specified type.
+    /// * `Err(NanoDBError::NotAnObject)` - If the JSON data is not an object.
+    /// * `Err(NanoDBError::KeyNotFound(key))` - If `key` does not exist in the JSON data.
+    /// * `Err(NanoDBError::TypeMismatch)` - If the value cannot be converted, see `Tree::into`.
+    pub async fn get_typed<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<T, NanoDBError> {
+        let data = self._read_lock().await;
+        let value = data
+            .as_object()
+            .ok_or_else(|| NanoDBError::NotAnObject(key.to_string()))?
+            .get(key)
+            .ok_or_else(|| NanoDBError::KeyNotFound(key.to_string()))?;
+        T::deserialize(value).map_err(|e| NanoDBError::TypeMismatch {
+            expected: std::any::type_name::<T>().to_string(),
+            actual: TreeType::of(value).as_str().to_string(),
+            detail: e.to_string(),
+        })
+    }
+
+    /// Retrieves the value at a dotted path (e.g. `"key3.versions[0]"`) in the JSON data of the NanoDB instance.
+    ///
+    /// Unlike `data().await.get_path(path)`, only the value at `path` is cloned, not the whole document.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path to retrieve the value for. The grammar is the same as for `Tree::get_path`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the value at `path`.
+    /// * `Err(NanoDBError)` - If `path` cannot be parsed or does not resolve (see `Tree::get_path`).
+    pub async fn get_path(&self, path: &str) -> Result<Tree, NanoDBError> {
+        let steps = tree_helper::parse_path(path)?;
+        let data = self._read_lock().await;
+        let value = tree_helper::resolve(&data, &[], &steps)?;
+        Ok(Tree::new(value.clone(), steps))
+    }
+
+    /// Checks whether a dotted path (e.g. `"key3.versions[0]"`) resolves in the JSON data of the NanoDB instance.
+    ///
+    /// Nothing is cloned. A path that cannot be parsed or that does not resolve yields `false`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path to check. The grammar is the same as for `Tree::get_path`.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether a value exists at `path`.
+    pub async fn exists(&self, path: &str) -> bool {
+        let Ok(steps) = tree_helper::parse_path(path) else {
+            return false;
+        };
+        let data = self._read_lock().await;
+        tree_helper::resolve(&data, &[], &steps).is_ok()
+    }
+
+    /// Sets the value at a dotted path (e.g. `"key3.versions[1]"`) in the JSON data of the NanoDB instance.
+    ///
+    /// The parent of the last path segment must already exist. If the last segment is a key, the value
+    /// is inserted into (or replaced in) the parent object. If it is an index, the array element at that
+    /// index is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path to set the value at. The grammar is the same as for `Tree::get_path`.
+    /// * `value` - The value to set. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If `path` is empty, cannot be parsed or an intermediate segment does not exist.
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index along the path is out of bounds.
+    /// * `Err(NanoDBError::NotAnObject)` - If the last segment is a key but the parent is not an object.
+    /// * `Err(NanoDBError::NotAnArray)` - If the last segment is an index but the parent is not an array.
+    pub async fn set_path<T: Serialize>(
+        &mut self,
+        path: &str,
+        value: T,
+    ) -> Result<(), NanoDBError> {
+        let steps = tree_helper::parse_path(path)?;
+        if steps.is_empty() {
+            return Err(NanoDBError::InvalidJSONPath);
+        }
+        let value = serde_json::to_value(value)?;
+
+        let mut write_guard = self._write_lock().await;
+        self._commit(&mut write_guard, JournalOp::SetPath { path: steps, value })
+            .await
+    }
+
     /// Executes an atomic query to the db, ensuring that the query either fully completes
     /// or is entirely rolled back in case of an error, maintaining the integrity of the database.
     /// This function is designed to handle operations that must be executed as a single,
@@ -137,142 +592,2154 @@ impl NanoDB {
     pub async fn update(&self) -> WriteGuardedTree<'_> {
         let write_guard = self._write_lock().await;
         let write_guard_value: Value = write_guard.clone();
-        WriteGuardedTree::new(write_guard, write_guard_value)
+        WriteGuardedTree::new(
+            write_guard,
+            write_guard_value,
+            &self.dirty,
+            self._guard_journal(),
+        )
     }
 
-    /// Inserts a key-value pair into the JSON data of the NanoDB instance.
+    /// Runs a closure against a write-guarded tree as a single transaction.
+    ///
+    /// The current JSON data is snapshotted before the closure runs. If the closure returns an error,
+    /// the snapshot is restored before the write lock is released, so other readers never observe a
+    /// partially applied transaction. If it returns `Ok`, all changes are kept.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to insert the value for.
-    /// * `value` - The value to insert. This value must implement the `Serialize` trait.
+    /// * `f` - The closure to run. It receives a write-guarded tree pointing to the root of the JSON data.
     ///
     /// # Returns
     ///
-    /// * `Ok(())` - If the operation was successful.
-    /// * `Err(NanoDBError::RwLockReadError)` - If there was an error acquiring the write lock.
-    /// * `Err(serde_json::Error)` - If there was an error serializing `value`.
-    pub async fn insert<T: Serialize>(&mut self, key: &str, value: T) -> Result<(), NanoDBError> {
+    /// * `Ok(())` - If the closure succeeded and its changes were committed.
+    /// * `Err(NanoDBError)` - The error returned by the closure, after the changes were rolled back.
+    pub async fn transaction<F>(&mut self, f: F) -> Result<(), NanoDBError>
+    where
+        F: FnOnce(&mut WriteGuardedTree) -> Result<(), NanoDBError>,
+    {
         let write_guard = self._write_lock().await;
-        let write_guard_value: Value = write_guard.clone();
-        let mut tree = WriteGuardedTree::new(write_guard, write_guard_value);
-        tree.insert(key, value)?;
+        let snapshot: Value = write_guard.clone();
+        let was_dirty = self.dirty.load(Ordering::Acquire);
+        // the transaction is journaled as a whole once it succeeded, not operation by operation
+        let mut tree = WriteGuardedTree::new(write_guard, snapshot.clone(), &self.dirty, None);
+        if let Err(e) = f(&mut tree) {
+            tree.rollback(snapshot, was_dirty);
+            return Err(e);
+        }
+        if self.journal.is_some() {
+            let op = JournalOp::Replace {
+                value: tree.guarded_value().clone(),
+            };
+            if let Err(e) = self._journal(&op).await {
+                tree.rollback(snapshot, was_dirty);
+                return Err(e);
+            }
+        }
         Ok(())
     }
 
-    /// Removes a key-value pair from the JSON object stored in the NanoDB instance.
+    /// Returns a read-guarded tree, giving up if the read lock cannot be acquired within `dur`.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to remove the value for.
+    /// * `dur` - The maximum time to wait for the read lock.
     ///
     /// # Returns
     ///
-    /// * `Ok(())` - If the removal was successful.
-    /// * `Err(NanoDBError)` - If there was an error during the removal.
-    pub async fn remove(&mut self, key: &str) -> Result<(), NanoDBError> {
-        let write_guard = self._write_lock().await;
-        let write_guard_value: Value = write_guard.clone();
-        let mut tree = WriteGuardedTree::new(write_guard, write_guard_value);
-        tree.remove(key)?;
-        Ok(())
+    /// * `Ok(ReadGuardedTree)` - A new ReadGuardedTree instance with the read lock and the JSON data.
+    /// * `Err(NanoDBError::LockTimeout)` - If the read lock could not be acquired in time.
+    pub async fn read_timeout(&self, dur: Duration) -> Result<ReadGuardedTree<'_>, NanoDBError> {
+        let read_guard = tokio::time::timeout(dur, self._read_lock())
+            .await
+            .map_err(|_| NanoDBError::LockTimeout)?;
+        let read_guard_value: Value = read_guard.clone();
+        Ok(ReadGuardedTree::new(read_guard, read_guard_value))
     }
 
-    /// Inserts a Tree (other) into the JSON data of the NanoDB instance.
-    /// It does so by respecting the path of the other Tree instance.
-    /// Current value at the path is replaced by the value of the other Tree instance.
+    /// Returns a write-guarded tree, giving up if the write lock cannot be acquired within `dur`.
     ///
     /// # Arguments
     ///
-    /// * `tree` - The Tree to merge into the JSON data.
+    /// * `dur` - The maximum time to wait for the write lock.
     ///
     /// # Returns
     ///
-    /// * `Ok(())` - If the operation was successful.
-    /// * `Err(NanoDBError::RwLockWriteError)` - If there was an error acquiring the write lock.
-    /// * `Err(NanoDBError::InvalidJSONPath)` - If the path does not exist in the JSON data or if a path step is not valid for the current value (e.g., using a key on an array or an index on an object).
-    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
-    pub async fn insert_tree(&mut self, other: Tree) -> Result<(), NanoDBError> {
-        let mut write_guard = self._write_lock().await;
-        // wrap data into a tree to use the merge from method
-        let mut current_tree = Tree::new(write_guard.clone(), vec![]);
-        current_tree.merge_from(other)?;
-
-        // update the current write guarded value
-        *write_guard = current_tree.inner();
-
-        Ok(())
+    /// * `Ok(WriteGuardedTree)` - A new WriteGuardedTree instance with the write lock and the JSON data.
+    /// * `Err(NanoDBError::LockTimeout)` - If the write lock could not be acquired in time.
+    pub async fn update_timeout(&self, dur: Duration) -> Result<WriteGuardedTree<'_>, NanoDBError> {
+        let write_guard = tokio::time::timeout(dur, self._write_lock())
+            .await
+            .map_err(|_| NanoDBError::LockTimeout)?;
+        let write_guard_value: Value = write_guard.clone();
+        Ok(WriteGuardedTree::new(
+            write_guard,
+            write_guard_value,
+            &self.dirty,
+            self._guard_journal(),
+        ))
     }
 
-    /// Writes the JSON data of the NanoDB instance to the file at its path.
+    /// Attempts to acquire a read-guarded tree without waiting.
     ///
     /// # Returns
     ///
-    /// * `Ok(())` - If the operation was successful.
-    /// * `Err(NanoDBError::RwLockWriteError)` - If there was an error acquiring the write lock.
-    /// * `Err(serde_json::Error)` - If there was an error serializing the JSON data.
-    /// * `Err(std::io::Error)` - If there was an error writing the data to the file.
-    pub async fn write(&mut self) -> Result<(), NanoDBError> {
-        let path = self.path.clone();
-        let data_guard = self._write_lock().await;
-        let contents = serde_json::to_string_pretty(&*data_guard)?;
-        tokio::fs::write(path, contents).await?;
-        Ok(())
+    /// * `Some(ReadGuardedTree)` - If the read lock could be acquired immediately.
+    /// * `None` - If the write lock is currently held.
+    pub fn try_read(&self) -> Option<ReadGuardedTree<'_>> {
+        let read_guard = self.data.try_read().ok()?;
+        let read_guard_value: Value = read_guard.clone();
+        Some(ReadGuardedTree::new(read_guard, read_guard_value))
     }
 
-    async fn _write_lock(&self) -> RwLockWriteGuard<'_, Value> {
-        self.data.write().await
+    /// Attempts to acquire a write-guarded tree without waiting.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(WriteGuardedTree)` - If the write lock could be acquired immediately.
+    /// * `None` - If the lock is currently held by a reader or writer.
+    pub fn try_update(&self) -> Option<WriteGuardedTree<'_>> {
+        let write_guard = self._try_write_lock()?;
+        let write_guard_value: Value = write_guard.clone();
+        Some(WriteGuardedTree::new(
+            write_guard,
+            write_guard_value,
+            &self.dirty,
+            self._guard_journal(),
+        ))
     }
 
-    async fn _read_lock(&self) -> RwLockReadGuard<'_, Value> {
-        self.data.read().await
-    }
+    /// Inserts a key-value pair into the JSON data of the NanoDB instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert the value for.
+    /// * `value` - The value to insert. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::RwLockReadError)` - If there was an error acquiring the write lock.
+    /// * `Err(serde_json::Error)` - If there was an error serializing `value`.
+    pub async fn insert<T: Serialize>(&mut self, key: &str, value: T) -> Result<(), NanoDBError> {
+        let value = serde_json::to_value(value)?;
+        let mut write_guard = self._write_lock().await;
+        let op = JournalOp::Insert {
+            key: key.to_string(),
+            value,
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Validates the JSON data of the NanoDB instance against a JSON Schema.
+    ///
+    /// Requires the `schema` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The JSON Schema to validate against.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the JSON data is valid.
+    /// * `Err(NanoDBError::InvalidSchema)` - If `schema` is not a valid JSON Schema.
+    /// * `Err(NanoDBError::SchemaViolation)` - If the JSON data is invalid, listing every violation.
+    #[cfg(feature = "schema")]
+    pub async fn validate(&self, schema: &Value) -> Result<(), NanoDBError> {
+        let validator = compile_schema(schema)?;
+        let data = self._read_lock().await;
+        check_schema(&validator, &data)
+    }
+
+    /// Sets the JSON Schema that `insert_validated` checks writes against.
+    ///
+    /// Requires the `schema` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The JSON Schema to store.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the schema was stored.
+    /// * `Err(NanoDBError::InvalidSchema)` - If `schema` is not a valid JSON Schema.
+    #[cfg(feature = "schema")]
+    pub fn set_schema(&mut self, schema: &Value) -> Result<(), NanoDBError> {
+        self.schema = Some(Arc::new(compile_schema(schema)?));
+        Ok(())
+    }
+
+    /// Inserts a key-value pair into the JSON data of the NanoDB instance if the result satisfies the
+    /// schema set with `set_schema`.
+    ///
+    /// If the insert would violate the schema, the data is left unchanged. Without a schema this behaves
+    /// like `insert`. Requires the `schema` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert the value for.
+    /// * `value` - The value to insert. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::SchemaViolation)` - If the data would no longer satisfy the schema.
+    /// * `Err(NanoDBError::NotAnObject)` - If the JSON data is not an object.
+    /// * `Err(serde_json::Error)` - If there was an error serializing `value`.
+    #[cfg(feature = "schema")]
+    pub async fn insert_validated<T: Serialize>(
+        &mut self,
+        key: &str,
+        value: T,
+    ) -> Result<(), NanoDBError> {
+        let op = JournalOp::Insert {
+            key: key.to_string(),
+            value: serde_json::to_value(value)?,
+        };
+        let mut write_guard = self._write_lock().await;
+        let mut candidate = write_guard.clone();
+        op.clone().apply(&mut candidate)?;
+        if let Some(validator) = &self.schema {
+            check_schema(validator, &candidate)?;
+        }
+        self._journal(&op).await?;
+        *write_guard = candidate;
+        self.dirty.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Inserts multiple key-value pairs into the JSON data of the NanoDB instance, acquiring the write lock only once.
+    ///
+    /// All values are serialized before the lock is taken, so either all pairs are inserted or none are.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The key-value pairs to insert. The values must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::NotAnObject)` - If the JSON data is not an object.
+    /// * `Err(serde_json::Error)` - If there was an error serializing a value.
+    pub async fn insert_many<T: Serialize>(
+        &mut self,
+        entries: impl IntoIterator<Item = (String, T)>,
+    ) -> Result<(), NanoDBError> {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| Ok((key, serde_json::to_value(value)?)))
+            .collect::<Result<Vec<(String, Value)>, NanoDBError>>()?;
+
+        let mut write_guard = self._write_lock().await;
+        self._commit(&mut write_guard, JournalOp::InsertMany { entries })
+            .await
+    }
+
+    /// Removes a key-value pair from the JSON object stored in the NanoDB instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the removal was successful.
+    /// * `Err(NanoDBError)` - If there was an error during the removal.
+    pub async fn remove(&mut self, key: &str) -> Result<(), NanoDBError> {
+        let mut write_guard = self._write_lock().await;
+        let map = write_guard
+            .as_object()
+            .ok_or_else(|| NanoDBError::NotAnObject(key.to_string()))?;
+        if !map.contains_key(key) {
+            return Err(NanoDBError::KeyNotFound(key.to_string()));
+        }
+        let op = JournalOp::Remove {
+            key: key.to_string(),
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Removes all data of the NanoDB instance, leaving an empty JSON object.
+    ///
+    /// Like every other mutation, this only changes the in-memory data; the backing file is left untouched
+    /// until `write` is called.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the data was cleared.
+    pub async fn clear(&mut self) -> Result<(), NanoDBError> {
+        let mut write_guard = self._write_lock().await;
+        self._commit(&mut write_guard, JournalOp::Clear).await
+    }
+
+    /// Inserts a Tree (other) into the JSON data of the NanoDB instance.
+    /// It does so by respecting the path of the other Tree instance.
+    /// Current value at the path is replaced by the value of the other Tree instance.
+    /// A root tree (see `Tree::is_root`) replaces the whole JSON data and therefore has to be an object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The Tree to merge into the JSON data.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::RwLockWriteError)` - If there was an error acquiring the write lock.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If the path does not exist in the JSON data, if a path step is not valid for the current value (e.g., using a key on an array or an index on an object), or if the tree is a root tree but not an object.
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
+    pub async fn insert_tree(&mut self, other: Tree) -> Result<(), NanoDBError> {
+        if other.is_root() && !other.inner().is_object() {
+            return Err(NanoDBError::InvalidJSONPath);
+        }
+        let mut write_guard = self._write_lock().await;
+        let op = JournalOp::InsertTree {
+            path: other.path(),
+            value: other.inner(),
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Recursively merges a Tree (other) into the JSON data of the NanoDB instance.
+    /// It does so by respecting the path of the other Tree instance.
+    /// Objects are merged key by key so that sibling keys are kept, following the same rules as `Tree::merge_deep_from`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The Tree to merge into the JSON data.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If the path does not exist in the JSON data or if a path step is not valid for the current value (e.g., using a key on an array or an index on an object).
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
+    pub async fn merge_deep(&mut self, other: Tree) -> Result<(), NanoDBError> {
+        let mut write_guard = self._write_lock().await;
+        let op = JournalOp::MergeDeep {
+            path: other.path(),
+            value: other.inner(),
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Applies a JSON Patch (RFC 6902) to the JSON data of the NanoDB instance.
+    ///
+    /// The patch is an array of operations (`add`, `remove`, `replace`, `move`, `copy` and `test`) whose paths
+    /// are JSON Pointers, e.g. `[{"op": "replace", "path": "/key3/versions/0", "value": 1.1}]`. The operations
+    /// are applied in order under a single write lock. If any of them fails, including a failing `test`, none
+    /// of them takes effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `patch` - The JSON Patch document.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If all operations were applied.
+    /// * `Err(NanoDBError::PatchFailed { op, path })` - For the first operation that is malformed or cannot be applied.
+    ///   If `patch` is not an array, `op` is `<not an array>` and `path` is empty.
+    pub async fn apply_patch(&mut self, patch: &Value) -> Result<(), NanoDBError> {
+        let mut write_guard = self._write_lock().await;
+        let op = JournalOp::ApplyPatch {
+            patch: patch.clone(),
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Applies a JSON Merge Patch (RFC 7386) to the JSON data of the NanoDB instance, see `Tree::merge_patch`.
+    ///
+    /// # Arguments
+    ///
+    /// * `patch` - The merge patch document. As in RFC 7386, a patch that is not an object replaces the JSON data wholesale.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the patch was applied.
+    pub async fn merge_patch(&mut self, patch: &Value) -> Result<(), NanoDBError> {
+        let mut write_guard = self._write_lock().await;
+        let op = JournalOp::MergePatch {
+            patch: patch.clone(),
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Reads another file and recursively merges its contents into the JSON data of the NanoDB instance.
+    ///
+    /// This follows the same rules as `merge_deep`, which makes it easy to layer configuration, e.g.
+    /// to apply an overrides file on top of defaults. The format of the file is inferred from its
+    /// extension, see `Format::from_path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to merge.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(std::io::Error)` - If there was an error reading the file.
+    /// * `Err(serde_json::Error)` - If there was an error parsing the file contents as JSON.
+    pub async fn merge_file(&mut self, path: impl AsRef<Path>) -> Result<(), NanoDBError> {
+        let path = path.as_ref();
+        let contents = tokio::fs::read(path).await?;
+        let other = Format::from_path(path).decode(&contents)?;
+
+        let mut write_guard = self._write_lock().await;
+        let op = JournalOp::MergeDeep {
+            path: vec![],
+            value: other,
+        };
+        self._commit(&mut write_guard, op).await
+    }
+
+    /// Returns the number of top-level keys or array elements of the JSON data of the NanoDB instance.
+    ///
+    /// Unlike `data().await.len()`, this inspects the data under a read lock without cloning it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of top-level keys (object) or elements (array).
+    /// * `Err(NanoDBError::LenNotDefined)` - If the JSON data is neither an object nor an array.
+    pub async fn len(&self) -> Result<usize, NanoDBError> {
+        let data = self._read_lock().await;
+        match &*data {
+            Value::Array(arr) => Ok(arr.len()),
+            Value::Object(obj) => Ok(obj.len()),
+            _ => Err(NanoDBError::LenNotDefined(String::new())),
+        }
+    }
+
+    /// Returns the top-level keys of the JSON data of the NanoDB instance.
+    ///
+    /// Unlike `data().await.keys()`, this reads the keys under a read lock without cloning the data.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<String>)` - The top-level keys, in the order in which serde_json stores them.
+    /// * `Err(NanoDBError::NotAnObject)` - If the JSON data is not an object.
+    pub async fn keys(&self) -> Result<Vec<String>, NanoDBError> {
+        let data = self._read_lock().await;
+        match &*data {
+            Value::Object(map) => Ok(map.keys().cloned().collect()),
+            _ => Err(NanoDBError::NotAnObject(String::new())),
+        }
+    }
+
+    /// Checks if the JSON data of the NanoDB instance is an empty object or array.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bool)` - Whether the JSON data has no top-level keys or elements.
+    /// * `Err(NanoDBError::LenNotDefined)` - If the JSON data is neither an object nor an array.
+    pub async fn is_empty(&self) -> Result<bool, NanoDBError> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Watches the backing file for changes and reloads the in-memory data whenever it is modified.
+    ///
+    /// The returned receiver gets a message after every reload. Rapid successive file events are
+    /// debounced into a single reload. If the file cannot be parsed (e.g. while an editor is still
+    /// writing it), the current data is kept and no message is sent.
+    ///
+    /// The watcher runs on a background tokio task that shares the data with this instance, so the
+    /// NanoDB instance (or a clone of it) has to be kept alive to observe the reloads. Watching stops
+    /// once the receiver is dropped. Requires the `watch` feature.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Receiver<()>)` - A receiver that is notified after every reload.
+    /// * `Err(NanoDBError::NoBackingFile)` - If the NanoDB instance was created with `new_in_memory`.
+    /// * `Err(std::io::Error)` - If the file watcher could not be set up.
+    #[cfg(feature = "watch")]
+    pub fn watch(&self) -> Result<tokio::sync::mpsc::Receiver<()>, NanoDBError> {
+        use notify::Watcher;
+
+        const DEBOUNCE: Duration = Duration::from_millis(50);
+
+        let path = self.path.clone().ok_or(NanoDBError::NoBackingFile)?;
+        let file_name = path.file_name().map(|name| name.to_os_string());
+        // watch the parent directory, since atomic writes replace the file rather than modify it
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    let _ = event_tx.send(event);
+                }
+            })
+            .map_err(std::io::Error::other)?;
+        watcher
+            .watch(&dir, notify::RecursiveMode::NonRecursive)
+            .map_err(std::io::Error::other)?;
+
+        let (reload_tx, reload_rx) = tokio::sync::mpsc::channel(1);
+        let db = self.clone();
+        tokio::spawn(async move {
+            // keep the watcher alive for as long as the task runs
+            let _watcher = watcher;
+            let concerns_file = |event: &notify::Event| {
+                !event.kind.is_access()
+                    && event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name().map(|name| name.to_os_string()) == file_name)
+            };
+            while let Some(event) = event_rx.recv().await {
+                if !concerns_file(&event) {
+                    continue;
+                }
+                while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, event_rx.recv()).await {}
+
+                let Ok(contents) = tokio::fs::read(&path).await else {
+                    continue;
+                };
+                let Ok(data) = db._decode(&contents) else {
+                    continue;
+                };
+                {
+                    let mut write_guard = db._write_lock().await;
+                    if *write_guard == data {
+                        continue;
+                    }
+                    *write_guard = data;
+                    db.dirty.store(false, Ordering::Release);
+                }
+                if reload_tx.send(()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(reload_rx)
+    }
+
+    /// Sets the format used by `write`, `write_if_dirty` and `backup` to serialize the JSON data.
+    ///
+    /// Defaults to `WriteFormat::Pretty { indent: 2 }`.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format to use for subsequent writes.
+    pub fn set_write_format(&mut self, format: WriteFormat) {
+        self.write_format = format;
+    }
+
+    /// Enables journaling of mutations to the file at the given path.
+    ///
+    /// While enabled, every mutation is appended as a line of JSON to the journal before it is applied in memory;
+    /// if the append fails, the data is left unchanged. A `transaction` is journaled once, after its closure
+    /// succeeded. The methods of trees returned by `update`, `update_timeout` and `try_update` are synchronous
+    /// and must not block on disk I/O, so their changes are queued and appended ahead of the next journaled
+    /// operation, or by `flush_journal`; until then, a crash loses them. A successful `write` persists the data
+    /// and truncates the journal, so the journal always holds the operations since the last write. After a
+    /// crash, open the database, enable the journal again and call `replay_journal` to recover those operations.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the journal file. It is created on the first journaled operation.
+    pub fn enable_journal(&mut self, path: impl Into<PathBuf>) {
+        self.journal = Some(path.into());
+    }
+
+    /// Appends the changes made through write-guarded trees since the last journaled operation to the journal.
+    ///
+    /// See `enable_journal`. Does nothing if journaling is not enabled or no changes are queued.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the queued changes were appended and synced to disk.
+    /// * `Err(std::io::Error)` - If the journal could not be written. The changes stay queued.
+    pub async fn flush_journal(&self) -> Result<(), NanoDBError> {
+        // guards queue their changes while holding the write lock, so the read lock keeps the queue in order
+        let _read_guard = self._read_lock().await;
+        self._journal_pending(None).await
+    }
+
+    /// Applies the operations recorded in the journal to the JSON data of the NanoDB instance.
+    ///
+    /// The journal is left in place until the next successful `write`. A trailing line that cannot be
+    /// parsed (e.g. because the process crashed while appending it) is ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of replayed operations. `0` if journaling is not enabled or the journal does not exist.
+    /// * `Err(NanoDBError::JournalReplayFailed)` - If a line of the journal cannot be parsed or its operation cannot be
+    ///   applied. The error names the line; the JSON data is left unchanged.
+    pub async fn replay_journal(&mut self) -> Result<usize, NanoDBError> {
+        let Some(path) = &self.journal else {
+            return Ok(0);
+        };
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        // keep the 1-based line numbers of the file for error reporting
+        let lines: Vec<(usize, &str)> = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| (i + 1, l))
+            .collect();
+        let mut write_guard = self._write_lock().await;
+        // replay into a copy so that a failing operation leaves the data untouched
+        let mut replayed_data = write_guard.clone();
+        let mut replayed = 0;
+        for (i, (number, line)) in lines.iter().enumerate() {
+            let failed = |e: NanoDBError| NanoDBError::JournalReplayFailed {
+                line: *number,
+                source: Box::new(e),
+            };
+            let op: JournalOp = match serde_json::from_str(line) {
+                Ok(op) => op,
+                Err(_) if i + 1 == lines.len() => break,
+                Err(e) => return Err(failed(e.into())),
+            };
+            op.apply(&mut replayed_data).map_err(failed)?;
+            replayed += 1;
+        }
+        if replayed > 0 {
+            *write_guard = replayed_data;
+            self.dirty.store(true, Ordering::Release);
+        }
+        Ok(replayed)
+    }
+
+    /// Sets the maximum size of the serialized data in bytes.
+    ///
+    /// Once set, `write` and `write_if_dirty` fail with `NanoDBError::SizeLimitExceeded` instead of writing
+    /// data that would exceed the limit. The size is measured after encoding (and compression, if enabled),
+    /// i.e. it is the size of the file that would be written.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of bytes of the backing file.
+    pub fn set_max_bytes(&mut self, limit: usize) {
+        self.max_bytes = Some(limit);
+    }
+
+    /// Writes the JSON data of the NanoDB instance to the file at its path.
+    ///
+    /// The data is first written to a temporary file next to the target (e.g. `.file.json.tmp`),
+    /// which is then renamed over the target. On POSIX filesystems this replaces the file atomically,
+    /// so an interrupted write never leaves a truncated file behind.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::RwLockWriteError)` - If there was an error acquiring the write lock.
+    /// * `Err(NanoDBError::NoBackingFile)` - If the NanoDB instance was created with `new_in_memory`.
+    /// * `Err(NanoDBError::SizeLimitExceeded)` - If the serialized data exceeds the limit set with `set_max_bytes`.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the JSON data.
+    /// * `Err(std::io::Error)` - If there was an error writing the data to the file.
+    pub async fn write(&mut self) -> Result<(), NanoDBError> {
+        let data_guard = self._write_lock().await;
+        self._write_to_disk(&data_guard).await?;
+        self.dirty.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    /// Writes the JSON data of the NanoDB instance to a new path and binds the instance to it.
+    ///
+    /// Unlike `backup`, subsequent calls to `write` target `new_path` instead of the previous file, which is
+    /// left untouched. The data is encoded like in `write`. If the write fails, the instance stays bound to its
+    /// previous path.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_path` - The path to write the data to. This argument is converted into a `PathBuf`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::SizeLimitExceeded)` - If the serialized data exceeds the limit set with `set_max_bytes`.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the JSON data.
+    /// * `Err(std::io::Error)` - If there was an error writing the data to `new_path`.
+    pub async fn save_as(&mut self, new_path: impl Into<PathBuf>) -> Result<(), NanoDBError> {
+        // lock through a separate handle so that the path can be swapped while the guard is held;
+        // the data itself is not modified, so the snapshot cache stays valid
+        let data = self.data.clone();
+        let data_guard = data.write().await;
+        let previous = self.path.replace(new_path.into());
+        if let Err(e) = self._write_to_disk(&data_guard).await {
+            self.path = previous;
+            return Err(e);
+        }
+        self.dirty.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    /// Writes the JSON data of the NanoDB instance to the file at its path, but only if it has been
+    /// modified since it was last read from or written to disk.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - If the data was modified and has been written.
+    /// * `Ok(false)` - If the data was unchanged and no write occurred.
+    /// * `Err(NanoDBError::NoBackingFile)` - If the data was modified but the NanoDB instance was created with `new_in_memory`.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the JSON data.
+    /// * `Err(std::io::Error)` - If there was an error writing the data to the file.
+    pub async fn write_if_dirty(&mut self) -> Result<bool, NanoDBError> {
+        let data_guard = self._write_lock().await;
+        if !self.dirty.load(Ordering::Acquire) {
+            return Ok(false);
+        }
+        self._write_to_disk(&data_guard).await?;
+        self.dirty.store(false, Ordering::Release);
+        Ok(true)
+    }
+
+    /// Writes the current JSON data of the NanoDB instance to the given writer.
+    ///
+    /// The data is serialized using the configured `WriteFormat`, pretty-printed by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the JSON data to, e.g. stdout or a network stream.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the JSON data.
+    /// * `Err(std::io::Error)` - If there was an error writing to `writer`.
+    pub async fn to_writer<W: std::io::Write>(&self, mut writer: W) -> Result<(), NanoDBError> {
+        let data_guard = self._read_lock().await;
+        let contents = self.write_format.serialize(&data_guard)?;
+        writer.write_all(&contents)?;
+        Ok(())
+    }
+
+    /// Writes a snapshot of the current JSON data of the NanoDB instance to another file.
+    ///
+    /// The in-memory data is serialized, not the file on disk, so modifications that have not been
+    /// written yet are included. The destination is written atomically, like `write`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest` - The path to write the backup to. This argument is converted into a `PathBuf`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the JSON data.
+    /// * `Err(std::io::Error)` - If there was an error writing the data to `dest`.
+    pub async fn backup(&self, dest: impl Into<PathBuf>) -> Result<(), NanoDBError> {
+        let dest = dest.into();
+        let data_guard = self._read_lock().await;
+        let contents = self._encode(&data_guard)?;
+        write_atomic(&dest, &contents).await
+    }
+
+    async fn _write_to_disk(&self, data: &Value) -> Result<(), NanoDBError> {
+        let path = self.path.as_ref().ok_or(NanoDBError::NoBackingFile)?;
+        let contents = self._encode(data)?;
+        if let Some(limit) = self.max_bytes {
+            if contents.len() > limit {
+                return Err(NanoDBError::SizeLimitExceeded {
+                    size: contents.len(),
+                    limit,
+                });
+            }
+        }
+        write_atomic(path, &contents).await?;
+        if let Some(journal) = &self.journal {
+            // the journaled and queued operations are part of the written data now
+            tokio::fs::write(journal, b"").await?;
+            self._pending_journal().clear();
+        }
+        Ok(())
+    }
+
+    /// Appends an operation to the journal, if journaling is enabled.
+    async fn _journal(&self, op: &JournalOp) -> Result<(), NanoDBError> {
+        self._journal_pending(Some(op)).await
+    }
+
+    /// Appends the operations queued by write-guarded trees, followed by `op`, to the journal.
+    ///
+    /// Has to be called while holding the data lock, so that no guard queues operations in the meantime.
+    /// If the append fails, the queued operations are put back so that a later append can retry them.
+    async fn _journal_pending(&self, op: Option<&JournalOp>) -> Result<(), NanoDBError> {
+        use tokio::io::AsyncWriteExt;
+
+        let Some(path) = &self.journal else {
+            return Ok(());
+        };
+        let pending = std::mem::take(&mut *self._pending_journal());
+        if pending.is_empty() && op.is_none() {
+            return Ok(());
+        }
+        let result = async {
+            let mut lines = Vec::new();
+            for queued in pending.iter().chain(op) {
+                lines.extend(queued.to_line()?);
+            }
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?;
+            file.write_all(&lines).await?;
+            file.sync_data().await?;
+            Ok(())
+        }
+        .await;
+        if result.is_err() {
+            let mut queue = self._pending_journal();
+            let newer = std::mem::replace(&mut *queue, pending);
+            queue.extend(newer);
+        }
+        result
+    }
+
+    fn _pending_journal(&self) -> std::sync::MutexGuard<'_, Vec<JournalOp>> {
+        self.pending_journal
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The queue for write-guarded trees to record their changes in, if journaling is enabled.
+    fn _guard_journal(&self) -> Option<&std::sync::Mutex<Vec<JournalOp>>> {
+        self.journal.as_ref().map(|_| &*self.pending_journal)
+    }
+
+    /// Applies `op` to the locked JSON data, appending it to the journal first if journaling is enabled.
+    ///
+    /// With a journal, the operation is applied to a copy that only replaces the data once the append
+    /// succeeded, so neither a failing operation nor a failing append changes the data.
+    async fn _commit(&self, data: &mut Value, op: JournalOp) -> Result<(), NanoDBError> {
+        if self.journal.is_some() {
+            let mut updated = data.clone();
+            op.clone().apply(&mut updated)?;
+            self._journal(&op).await?;
+            *data = updated;
+        } else {
+            op.apply(data)?;
+        }
+        self.dirty.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Replaces the in-memory data with the contents of the backing file, or an empty object if it does not exist.
+    fn _load(&mut self) -> Result<(), NanoDBError> {
+        let path = self.path.as_ref().ok_or(NanoDBError::NoBackingFile)?;
+        let data = if path.exists() {
+            self._decode(&std::fs::read(path)?)?
+        } else {
+            Value::Object(Default::default())
+        };
+        self.data = Arc::new(RwLock::new(data));
+        self.snapshot = Default::default();
+        Ok(())
+    }
+
+    fn _decode(&self, bytes: &[u8]) -> Result<Value, NanoDBError> {
+        #[cfg(feature = "gzip")]
+        if self.compressed {
+            use std::io::Read;
+
+            let mut decompressed = Vec::new();
+            flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+            return self.format.decode(&decompressed);
+        }
+        self.format.decode(bytes)
+    }
+
+    fn _encode(&self, data: &Value) -> Result<Vec<u8>, NanoDBError> {
+        let contents = self.format.encode(data, self.write_format)?;
+        #[cfg(feature = "gzip")]
+        if self.compressed {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&contents)?;
+            return Ok(encoder.finish()?);
+        }
+        Ok(contents)
+    }
+
+    async fn _write_lock(&self) -> RwLockWriteGuard<'_, Value> {
+        let write_guard = self.data.write().await;
+        // the data may change while the guard is held, so the cached snapshot is stale from here on
+        self._snapshot_cache().take();
+        write_guard
+    }
+
+    fn _try_write_lock(&self) -> Option<RwLockWriteGuard<'_, Value>> {
+        let write_guard = self.data.try_write().ok()?;
+        self._snapshot_cache().take();
+        Some(write_guard)
+    }
+
+    fn _snapshot_cache(&self) -> std::sync::MutexGuard<'_, Option<Arc<Value>>> {
+        self.snapshot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn _read_lock(&self) -> RwLockReadGuard<'_, Value> {
+        self.data.read().await
+    }
+}
+
+/// A mutating operation as recorded in the journal, one JSON object per line.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum JournalOp {
+    Insert { key: String, value: Value },
+    InsertMany { entries: Vec<(String, Value)> },
+    Remove { key: String },
+    SetPath { path: Vec<PathStep>, value: Value },
+    InsertTree { path: Vec<PathStep>, value: Value },
+    MergeDeep { path: Vec<PathStep>, value: Value },
+    ApplyPatch { patch: Value },
+    MergePatch { patch: Value },
+    Replace { value: Value },
+    Clear,
+}
+
+impl JournalOp {
+    /// Applies the operation to `data`, following the rules of the corresponding NanoDB method.
+    ///
+    /// Removing a key that does not exist is not an error, so that replaying a journal whose operations
+    /// have already been written is harmless for removals.
+    fn apply(self, data: &mut Value) -> Result<(), NanoDBError> {
+        match self {
+            JournalOp::Insert { key, value } => {
+                data.as_object_mut()
+                    .ok_or_else(|| NanoDBError::NotAnObject(key.clone()))?
+                    .insert(key, value);
+            }
+            JournalOp::InsertMany { entries } => {
+                data.as_object_mut()
+                    .ok_or_else(|| NanoDBError::NotAnObject(String::new()))?
+                    .extend(entries);
+            }
+            JournalOp::Remove { key } => {
+                data.as_object_mut()
+                    .ok_or_else(|| NanoDBError::NotAnObject(key.clone()))?
+                    .remove(&key);
+            }
+            JournalOp::SetPath { mut path, value } => {
+                let last = path.pop().ok_or(NanoDBError::InvalidJSONPath)?;
+                let parent = tree_helper::resolve_mut(data, &path)?;
+                match last {
+                    PathStep::Key(key) => {
+                        parent
+                            .as_object_mut()
+                            .ok_or_else(|| NanoDBError::NotAnObject(key.clone()))?
+                            .insert(key, value);
+                    }
+                    PathStep::Index(index) => {
+                        let element = parent
+                            .as_array_mut()
+                            .ok_or_else(|| {
+                                NanoDBError::NotAnArray(tree_helper::path_to_string(&path))
+                            })?
+                            .get_mut(index)
+                            .ok_or(NanoDBError::IndexOutOfBounds(index))?;
+                        *element = value;
+                    }
+                }
+            }
+            JournalOp::InsertTree { path, value } => {
+                // wrap data into a tree to use the merge from method
+                let mut current_tree = Tree::new(data.clone(), vec![]);
+                current_tree.merge_from(Tree::new(value, path))?;
+                *data = current_tree.inner();
+            }
+            JournalOp::MergeDeep { path, value } => {
+                let current = tree_helper::resolve_mut(data, &path)?;
+                tree_helper::merge_values(current, value, ArrayMerge::Concat);
+            }
+            JournalOp::ApplyPatch { patch } => tree_helper::apply_patch(data, &patch)?,
+            JournalOp::MergePatch { patch } => tree_helper::merge_patch(data, &patch),
+            JournalOp::Replace { value } => *data = value,
+            JournalOp::Clear => *data = Value::Object(Default::default()),
+        }
+        Ok(())
+    }
+
+    fn to_line(&self) -> Result<Vec<u8>, NanoDBError> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+/// Writes `contents` to a temporary file in the same directory as `path` and renames it over `path`.
+///
+/// The temporary file is removed if writing or renaming fails.
+async fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), NanoDBError> {
+    let tmp_path = tmp_path_for(path);
+    let result = async {
+        tokio::fs::write(&tmp_path, contents).await?;
+        tokio::fs::rename(&tmp_path, path).await
+    }
+    .await;
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "schema")]
+fn compile_schema(schema: &Value) -> Result<jsonschema::Validator, NanoDBError> {
+    jsonschema::validator_for(schema).map_err(|e| NanoDBError::InvalidSchema(e.to_string()))
+}
+
+#[cfg(feature = "schema")]
+fn check_schema(validator: &jsonschema::Validator, data: &Value) -> Result<(), NanoDBError> {
+    let violations: Vec<String> = validator
+        .iter_errors(data)
+        .map(|e| format!("{}: {}", e.instance_path, e))
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(NanoDBError::SchemaViolation(violations))
+    }
 }
 
-impl Clone for NanoDB {
-    fn clone(&self) -> Self {
-        Self {
-            path: self.path.clone(),
-            data: self.data.clone(),
+/// Returns the path of the temporary file used to atomically write `path` (e.g. `.file.json.tmp`).
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+impl Clone for NanoDB {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            data: self.data.clone(),
+            dirty: self.dirty.clone(),
+            format: self.format,
+            write_format: self.write_format,
+            max_bytes: self.max_bytes,
+            snapshot: self.snapshot.clone(),
+            journal: self.journal.clone(),
+            pending_journal: self.pending_journal.clone(),
+            temp_dir: self.temp_dir.clone(),
+            #[cfg(feature = "gzip")]
+            compressed: self.compressed,
+            #[cfg(feature = "schema")]
+            schema: self.schema.clone(),
+        }
+    }
+}
+
+impl Default for NanoDB {
+    /// Creates an empty in-memory NanoDB instance, see `new_in_memory`.
+    fn default() -> Self {
+        Self::new_in_memory(Value::Object(Default::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_new_from() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let db = NanoDB::new_from(&path, r#"{"key": "value"}"#).unwrap();
+        assert_eq!(db.data().await.get("key").unwrap().inner(), json!("value"));
+        assert_eq!(db.path, Some(path.clone()));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"key": "value"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_from_temp() {
+        let db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+        let path = db.path.clone().unwrap();
+        assert!(path.starts_with(std::env::temp_dir()));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"key": "value"}"#
+        );
+
+        // the file is removed together with the last clone
+        let clone = db.clone();
+        drop(db);
+        assert!(path.exists());
+        drop(clone);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_insert() {
+        let mut db = NanoDB::new_from_temp(r#"{}"#).unwrap();
+        db.insert("new_key", "new_value").await.unwrap();
+        assert_eq!(
+            db.data().await.get("new_key").unwrap().inner(),
+            json!("new_value")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let mut db = NanoDB::new_from_temp(r#"{}"#).unwrap();
+        db.insert("new_key", "new_value").await.unwrap();
+        db.remove("new_key").await.unwrap();
+        assert!(matches!(
+            db.data().await.get("new_key").unwrap_err(),
+            NanoDBError::KeyNotFound(_)
+        ));
+        assert!(matches!(
+            db.remove("new_key").await.unwrap_err(),
+            NanoDBError::KeyNotFound(_)
+        ));
+
+        let mut db = NanoDB::new_from_temp(r#"[1, 2]"#).unwrap();
+        assert!(matches!(
+            db.remove("key").await.unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_len_and_is_empty() {
+        let db = NanoDB::new_from_temp(r#"{"a": 1, "b": 2}"#).unwrap();
+        assert_eq!(db.len().await.unwrap(), 2);
+        assert!(!db.is_empty().await.unwrap());
+
+        let db = NanoDB::new_from_temp(r#"[]"#).unwrap();
+        assert_eq!(db.len().await.unwrap(), 0);
+        assert!(db.is_empty().await.unwrap());
+
+        let db = NanoDB::new_from_temp(r#"42"#).unwrap();
+        assert!(matches!(
+            db.len().await.unwrap_err(),
+            NanoDBError::LenNotDefined(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_with() {
+        let items: Vec<Value> = (0..10_000).map(|i| json!({ "id": i })).collect();
+        let db =
+            NanoDB::new_from_temp(&json!({ "outer": { "items": items } }).to_string()).unwrap();
+
+        let len = db
+            .read_with(|data| data["outer"]["items"].as_array().map(|arr| arr.len()))
+            .await;
+        assert_eq!(len, Some(10_000));
+
+        // the closure sees the live value, not a copy of it
+        let first = db.read_with(|data| data as *const Value).await;
+        let second = db.read_with(|data| data as *const Value).await;
+        assert_eq!(first, second);
+        let live = &*db._read_lock().await as *const Value;
+        assert_eq!(first, live);
+    }
+
+    #[tokio::test]
+    async fn test_write_if_dirty() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let mut db = NanoDB::open(&path).unwrap();
+
+        // a clean open does not touch the disk
+        assert!(!db.dirty.load(Ordering::Acquire));
+        assert!(!db.write_if_dirty().await.unwrap());
+        assert!(!path.exists());
+
+        db.insert("key", "value").await.unwrap();
+        assert!(db.dirty.load(Ordering::Acquire));
+        assert!(db.write_if_dirty().await.unwrap());
+        assert!(!db.dirty.load(Ordering::Acquire));
+        assert_eq!(
+            NanoDB::open(&path).unwrap().data().await.inner(),
+            json!({"key": "value"})
+        );
+        assert!(!db.write_if_dirty().await.unwrap());
+
+        // write guard operations and clones share the flag
+        db.clone().update().await.insert("other", 1).unwrap();
+        assert!(db.dirty.load(Ordering::Acquire));
+        db.write().await.unwrap();
+        assert!(!db.dirty.load(Ordering::Acquire));
+
+        db.merge_deep(Tree::new(json!({"key": "new"}), vec![]))
+            .await
+            .unwrap();
+        assert!(db.write_if_dirty().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_write_is_atomic() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let mut db = NanoDB::open(&path).unwrap();
+        db.insert("key", "value").await.unwrap();
+        db.write().await.unwrap();
+        assert!(!dir.path().join(".db.json.tmp").exists());
+
+        // make the temporary file unwritable to simulate a failure mid-write
+        std::fs::create_dir(dir.path().join(".db.json.tmp")).unwrap();
+        db.insert("key", "new_value").await.unwrap();
+        assert!(matches!(db.write().await.unwrap_err(), NanoDBError::Io(..)));
+        assert_eq!(
+            NanoDB::open(&path).unwrap().data().await.inner(),
+            json!({"key": "value"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_in_memory() {
+        let mut db = NanoDB::new_in_memory(json!({"key": "value"}));
+        assert!(db.path.is_none());
+        db.insert("new_key", [1, 2]).await.unwrap();
+        db.update().await.get("new_key").unwrap().push(3).unwrap();
+        assert_eq!(
+            db.data().await.inner(),
+            json!({"key": "value", "new_key": [1, 2, 3]})
+        );
+        assert!(matches!(
+            db.write().await.unwrap_err(),
+            NanoDBError::NoBackingFile
+        ));
+        assert!(matches!(
+            db.write_if_dirty().await.unwrap_err(),
+            NanoDBError::NoBackingFile
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_backup() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let backup_path = dir.path().join("backup.json");
+        let mut db = NanoDB::open(&path).unwrap();
+        db.insert("key", "value").await.unwrap();
+        db.write().await.unwrap();
+
+        db.insert("key", "unsaved").await.unwrap();
+        db.backup(&backup_path).await.unwrap();
+        assert_eq!(
+            NanoDB::open(&backup_path).unwrap().data().await.inner(),
+            json!({"key": "unsaved"})
+        );
+        assert_eq!(
+            NanoDB::open(&path).unwrap().data().await.inner(),
+            json!({"key": "value"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transaction() {
+        let mut db = NanoDB::new_from_temp(r#"{"a": 1, "b": [1, 2]}"#).unwrap();
+
+        let err = db
+            .transaction(|tree| {
+                tree.insert("a", 2)?;
+                tree.get("b")?.push(3)?.at(5)?;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, NanoDBError::IndexOutOfBounds(5)));
+        assert_eq!(db.data().await.inner(), json!({"a": 1, "b": [1, 2]}));
+        assert!(!db.dirty.load(Ordering::Acquire));
+
+        db.transaction(|tree| {
+            tree.insert("a", 2)?;
+            tree.get("b")?.push(3)?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(db.data().await.inner(), json!({"a": 2, "b": [1, 2, 3]}));
+        assert!(db.dirty.load(Ordering::Acquire));
+    }
+
+    #[tokio::test]
+    async fn test_write_format() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let mut db = NanoDB::open(&path).unwrap();
+        db.insert("key", [1, 2]).await.unwrap();
+
+        db.write().await.unwrap();
+        let default = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            default,
+            serde_json::to_string_pretty(&json!({"key": [1, 2]})).unwrap()
+        );
+
+        db.set_write_format(WriteFormat::Compact);
+        db.write().await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{"key":[1,2]}"#);
+
+        db.set_write_format(WriteFormat::Pretty { indent: 4 });
+        db.backup(dir.path().join("backup.json")).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("backup.json")).unwrap(),
+            "{\n    \"key\": [\n        1,\n        2\n    ]\n}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_or_create() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let db = NanoDB::open_or_create(&path).unwrap();
+        assert!(path.exists());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        assert_eq!(db.data().await.inner(), json!({}));
+
+        std::fs::write(&path, r#"{"key": "value"}"#).unwrap();
+        let db = NanoDB::open_or_create(&path).unwrap();
+        assert_eq!(db.data().await.inner(), json!({"key": "value"}));
+    }
+
+    #[tokio::test]
+    async fn test_try_update_and_try_read() {
+        let db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+
+        let guard = db.update().await;
+        assert!(db.try_update().is_none());
+        assert!(db.try_read().is_none());
+        guard.release_lock();
+
+        let read_guard = db.try_read().unwrap();
+        assert!(db.try_read().is_some());
+        assert!(db.try_update().is_none());
+        drop(read_guard);
+
+        let mut write_guard = db.try_update().unwrap();
+        write_guard.insert("key", "new_value").unwrap();
+        write_guard.release_lock();
+        assert_eq!(db.data().await.inner(), json!({"key": "new_value"}));
+    }
+
+    #[tokio::test]
+    async fn test_update_timeout_and_read_timeout() {
+        let db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+        let dur = Duration::from_millis(20);
+
+        let guard = db.update().await;
+        assert!(matches!(
+            db.update_timeout(dur).await.unwrap_err(),
+            NanoDBError::LockTimeout
+        ));
+        assert!(matches!(
+            db.read_timeout(dur).await.unwrap_err(),
+            NanoDBError::LockTimeout
+        ));
+        guard.release_lock();
+
+        let read_guard = db.read_timeout(dur).await.unwrap();
+        assert_eq!(read_guard.tree().inner(), json!({"key": "value"}));
+        drop(read_guard);
+        db.update_timeout(dur)
+            .await
+            .unwrap()
+            .insert("key", "new_value")
+            .unwrap();
+        assert_eq!(db.data().await.inner(), json!({"key": "new_value"}));
+    }
+
+    #[tokio::test]
+    async fn test_from_reader_and_to_writer() {
+        let input = std::io::Cursor::new(r#"{"key": [1, 2]}"#);
+        let mut db = NanoDB::from_reader(input).unwrap();
+        assert!(db.path.is_none());
+        db.insert("other", true).await.unwrap();
+
+        let mut output = std::io::Cursor::new(Vec::new());
+        db.to_writer(&mut output).await.unwrap();
+        output.set_position(0);
+        let db = NanoDB::from_reader(output).unwrap();
+        assert_eq!(
+            db.data().await.inner(),
+            json!({"key": [1, 2], "other": true})
+        );
+
+        assert!(matches!(
+            NanoDB::from_reader(std::io::Cursor::new("{")).unwrap_err(),
+            NanoDBError::DeserializeFromStr(..)
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn test_yaml_format() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "name: NanoDB\nversions:\n  - 1\n  - 2\n").unwrap();
+
+        let mut db = NanoDB::open(&path).unwrap();
+        assert_eq!(db.format, Format::Yaml);
+        let data = db.data().await;
+        assert_eq!(data.get("name").unwrap().as_str(), Some("NanoDB"));
+        assert_eq!(
+            data.get("versions").unwrap().at(1).unwrap().as_i64(),
+            Some(2)
+        );
+
+        db.update().await.get("versions").unwrap().push(3).unwrap();
+        db.write().await.unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("- 3"));
+
+        let db = NanoDB::open_with_format(&path, Format::Yaml).unwrap();
+        assert_eq!(
+            db.data().await.inner(),
+            json!({"name": "NanoDB", "versions": [1, 2, 3]})
+        );
+        assert!(matches!(
+            NanoDB::open_with_format(&path, Format::Json).unwrap_err(),
+            NanoDBError::DeserializeFromStr(..)
+        ));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn test_msgpack_format() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.msgpack");
+        let value = json!({"key1": "value1", "key2": {"inner": [1, 2.5, null, true]}});
+
+        let mut db = NanoDB::open_with_format(&path, Format::MessagePack).unwrap();
+        db.insert("key1", "value1").await.unwrap();
+        db.insert("key2", json!({"inner": [1, 2.5, null, true]}))
+            .await
+            .unwrap();
+        db.write().await.unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.len() < serde_json::to_vec(&value).unwrap().len());
+        assert!(serde_json::from_slice::<Value>(&bytes).is_err());
+
+        let db = NanoDB::open_with_format(&path, Format::MessagePack).unwrap();
+        assert_eq!(db.data().await.inner(), value);
+        assert_eq!(
+            db.data().await.get_path("key2.inner[1]").unwrap().as_f64(),
+            Some(2.5)
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_gzip_compression() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db.json.gz");
+        let items: Vec<Value> = (0..1_000)
+            .map(|i| json!({ "id": i, "name": "item" }))
+            .collect();
+
+        let mut db = NanoDB::open(&path).unwrap();
+        assert!(db.compressed);
+        db.insert("items", &items).await.unwrap();
+        db.write().await.unwrap();
+
+        let compressed = std::fs::read(&path).unwrap();
+        let plain = serde_json::to_string_pretty(&json!({ "items": items })).unwrap();
+        assert!(compressed.len() < plain.len());
+        assert!(serde_json::from_slice::<Value>(&compressed).is_err());
+
+        let db = NanoDB::open(&path).unwrap();
+        assert_eq!(db.data().await.get("items").unwrap().len().unwrap(), 1_000);
+        let db = NanoDB::open_compressed(&path).unwrap();
+        assert_eq!(db.data().await.inner(), json!({ "items": items }));
+    }
+
+    #[tokio::test]
+    async fn test_get_optional() {
+        let db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+        let tree = db.get_optional("key").await.unwrap().unwrap();
+        assert_eq!(tree, json!("value"));
+        assert_eq!(tree.path_string(), "key");
+        assert!(db.get_optional("missing").await.unwrap().is_none());
+
+        let db = NanoDB::new_from_temp(r#"[1, 2]"#).unwrap();
+        assert!(matches!(
+            db.get_optional("key").await.unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insert_tree_readme_usage() {
+        let mut db = NanoDB::new_from_temp(
+            r#"{"key3": {"name": "NanoDB", "versions": [1.0, 2.0]}, "key4": [1, 2, 3]}"#,
+        )
+        .unwrap();
+
+        let mut my_tree = db.data().await.get("key3").unwrap();
+        my_tree.insert("language", "Rust").unwrap();
+        db.insert_tree(my_tree).await.unwrap();
+        assert_eq!(
+            db.data().await.get("key3").unwrap(),
+            json!({"name": "NanoDB", "versions": [1.0, 2.0], "language": "Rust"})
+        );
+
+        let versions = db
+            .data()
+            .await
+            .get("key3")
+            .unwrap()
+            .get("versions")
+            .unwrap()
+            .push(3.1)
+            .unwrap();
+        db.insert_tree(versions).await.unwrap();
+        let numbers = db.data().await.get("key4").unwrap().push(4).unwrap();
+        db.insert_tree(numbers).await.unwrap();
+        assert_eq!(
+            db.get_path("key3.versions").await.unwrap(),
+            json!([1.0, 2.0, 3.1])
+        );
+        assert_eq!(db.get_path("key4").await.unwrap(), json!([1, 2, 3, 4]));
+
+        // root trees replace the whole document and must be objects
+        db.insert_tree(Tree::from_value(json!({"key": "value"})))
+            .await
+            .unwrap();
+        assert_eq!(db.data().await.inner(), json!({"key": "value"}));
+        assert!(matches!(
+            db.insert_tree(Tree::from_value(json!([1, 2])))
+                .await
+                .unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+        assert_eq!(db.data().await.inner(), json!({"key": "value"}));
+    }
+
+    #[cfg(feature = "schema")]
+    #[tokio::test]
+    async fn test_schema_validation() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"key2": {"type": "integer"}},
+            "required": ["key2"]
+        });
+
+        let db = NanoDB::new_from_temp(r#"{"key2": 42}"#).unwrap();
+        db.validate(&schema).await.unwrap();
+
+        let db = NanoDB::new_from_temp(r#"{"key2": "42"}"#).unwrap();
+        match db.validate(&schema).await.unwrap_err() {
+            NanoDBError::SchemaViolation(violations) => {
+                assert_eq!(violations.len(), 1);
+                assert!(violations[0].starts_with("/key2"));
+            }
+            e => panic!("unexpected error: {}", e),
         }
+        assert!(matches!(
+            db.validate(&json!({"type": 1})).await.unwrap_err(),
+            NanoDBError::InvalidSchema(_)
+        ));
+
+        let mut db = NanoDB::new_from_temp(r#"{"key2": 42}"#).unwrap();
+        db.set_schema(&schema).unwrap();
+        db.insert_validated("key1", "value").await.unwrap();
+        db.insert_validated("key2", 43).await.unwrap();
+        assert!(matches!(
+            db.insert_validated("key2", "not a number")
+                .await
+                .unwrap_err(),
+            NanoDBError::SchemaViolation(_)
+        ));
+        assert_eq!(
+            db.data().await.inner(),
+            json!({"key1": "value", "key2": 43})
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
+    #[tokio::test]
+    async fn test_merge_file() {
+        let dir = tempdir().unwrap();
+        let overrides = dir.path().join("overrides.json");
+        std::fs::write(
+            &overrides,
+            r#"{"db": {"port": 6543}, "debug": true, "plugins": ["extra"]}"#,
+        )
+        .unwrap();
+
+        let mut db = NanoDB::new_from_temp(
+            r#"{"db": {"host": "localhost", "port": 5432}, "debug": false, "plugins": ["base"]}"#,
+        )
+        .unwrap();
+        db.merge_file(&overrides).await.unwrap();
+        assert_eq!(
+            db.data().await.inner(),
+            json!({
+                "db": {"host": "localhost", "port": 6543},
+                "debug": true,
+                "plugins": ["base", "extra"]
+            })
+        );
+
+        assert!(matches!(
+            db.merge_file(dir.path().join("missing.json"))
+                .await
+                .unwrap_err(),
+            NanoDBError::Io(..)
+        ));
+        std::fs::write(&overrides, "{").unwrap();
+        assert!(matches!(
+            db.merge_file(&overrides).await.unwrap_err(),
+            NanoDBError::DeserializeFromStr(..)
+        ));
+    }
 
     #[tokio::test]
-    async fn test_new_from() {
-        let db = NanoDB::new_from("/path/to/file.json", r#"{"key": "value"}"#).unwrap();
+    async fn test_keys() {
+        let db = NanoDB::new_from_temp(r#"{"b": 1, "a": {"c": 2}}"#).unwrap();
+        assert_eq!(db.keys().await.unwrap(), vec!["a", "b"]);
+
+        let db = NanoDB::new_from_temp(r#"[1, 2]"#).unwrap();
+        assert!(matches!(
+            db.keys().await.unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insert_many() {
+        let db = NanoDB::new_from_temp(r#"{"a": 0}"#).unwrap();
+
+        // hold a read guard so that the batch has to wait for the write lock
+        let read_guard = db.read().await;
+        let mut writer = db.clone();
+        let batch = tokio::spawn(async move {
+            let entries = (0..100).map(|i| (format!("key{}", i), i));
+            writer.insert_many(entries).await
+        });
+        tokio::task::yield_now().await;
+        assert!(!batch.is_finished());
+
+        // the lock is fair, so this reader is queued behind the batch; had the batch released the lock
+        // between entries, the reader would get in between and observe a partial batch
+        let reader = db.clone();
+        let observed = tokio::spawn(async move { reader.len().await.unwrap() });
+        tokio::task::yield_now().await;
+        assert_eq!(read_guard.len().unwrap(), 1);
+        drop(read_guard);
+
+        batch.await.unwrap().unwrap();
+        assert_eq!(observed.await.unwrap(), 101);
+
+        assert_eq!(db.len().await.unwrap(), 101);
+        assert_eq!(db.get_path("key0").await.unwrap(), json!(0));
+        assert_eq!(db.get_path("key99").await.unwrap(), json!(99));
+        assert_eq!(db.get_path("a").await.unwrap(), json!(0));
+
+        let mut db = NanoDB::new_from_temp(r#"[]"#).unwrap();
+        assert!(matches!(
+            db.insert_many(vec![("a".to_string(), 1)])
+                .await
+                .unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exists() {
+        let db = NanoDB::new_from_temp(
+            r#"{"key1": "value1", "key3": {"name": "NanoDB", "versions": [1.0, 2.0, 3.0]}}"#,
+        )
+        .unwrap();
+
+        assert!(db.exists("key1").await);
+        assert!(db.exists("key3.versions[0]").await);
+        assert!(!db.exists("key0").await);
+        assert!(!db.exists("key3.versions[99]").await);
+        assert!(!db.exists("key1.inner").await);
+        assert!(!db.exists("key3..name").await);
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let mut db = NanoDB::open(&path).unwrap();
+        db.insert("key1", 1).await.unwrap();
+        db.insert("key2", "two").await.unwrap();
+        db.insert("key3", vec![3]).await.unwrap();
+        db.write().await.unwrap();
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+
+        db.clear().await.unwrap();
+        assert_eq!(db.len().await.unwrap(), 0);
+        assert_eq!(db.data().await.inner(), json!({}));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), on_disk);
+
+        db.write().await.unwrap();
+        assert_eq!(NanoDB::open(&path).unwrap().len().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_default() {
+        let mut db = NanoDB::default();
+        assert!(db.path.is_none());
+        assert_eq!(db.len().await.unwrap(), 0);
+
+        db.insert("key", "value").await.unwrap();
         assert_eq!(db.data().await.get("key").unwrap().inner(), json!("value"));
+        assert!(matches!(db.write().await, Err(NanoDBError::NoBackingFile)));
     }
 
     #[tokio::test]
-    async fn test_insert() {
-        let mut db = NanoDB::new_from("/path/to/file.json", r#"{}"#).unwrap();
-        db.insert("new_key", "new_value").await.unwrap();
+    async fn test_max_bytes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let mut db = NanoDB::open(&path).unwrap();
+        db.set_write_format(WriteFormat::Compact);
+        db.set_max_bytes(32);
+
+        db.insert("key", "value").await.unwrap();
+        db.write().await.unwrap();
         assert_eq!(
-            db.data().await.get("new_key").unwrap().inner(),
-            json!("new_value")
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"key":"value"}"#
+        );
+
+        db.insert("long", "a".repeat(32)).await.unwrap();
+        let err = db.write().await.unwrap_err();
+        assert!(matches!(
+            err,
+            NanoDBError::SizeLimitExceeded { size, limit: 32 } if size > 32
+        ));
+        assert!(db.dirty.load(Ordering::Acquire));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"key":"value"}"#
+        );
+
+        db.remove("long").await.unwrap();
+        db.write().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let mut db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+
+        let first = db.snapshot().await;
+        let second = db.clone().snapshot().await;
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, json!({"key": "value"}));
+
+        db.insert("key", "changed").await.unwrap();
+        let third = db.snapshot().await;
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(*first, json!({"key": "value"}));
+        assert_eq!(*third, json!({"key": "changed"}));
+
+        let mut tree = db.try_update().unwrap();
+        tree.insert("key", json!("updated")).unwrap();
+        tree.merge().unwrap();
+        drop(tree);
+        let fourth = db.snapshot().await;
+        assert!(!Arc::ptr_eq(&third, &fourth));
+        assert_eq!(*fourth, json!({"key": "updated"}));
+    }
+
+    #[tokio::test]
+    async fn test_journal_replay() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let journal = dir.path().join("data.journal");
+
+        let mut db = NanoDB::open(&path).unwrap();
+        db.enable_journal(&journal);
+        db.insert("key1", "value1").await.unwrap();
+        db.insert("key2", json!({"versions": [1]})).await.unwrap();
+        db.write().await.unwrap();
+        assert_eq!(std::fs::read_to_string(&journal).unwrap(), "");
+
+        db.insert("key3", 3).await.unwrap();
+        db.remove("key1").await.unwrap();
+        let versions = db
+            .data()
+            .await
+            .get("key2")
+            .unwrap()
+            .get("versions")
+            .unwrap();
+        db.merge_deep(Tree::new(json!([2]), versions.path()))
+            .await
+            .unwrap();
+        db.insert_tree(Tree::new(
+            json!("new"),
+            vec![PathStep::Key("key3".to_string())],
+        ))
+        .await
+        .unwrap();
+        db.apply_patch(&json!([{"op": "add", "path": "/key4", "value": [4]}]))
+            .await
+            .unwrap();
+        db.merge_patch(&json!({"key5": {"a": 1}})).await.unwrap();
+        db.insert_many(vec![("key6".to_string(), 6), ("key7".to_string(), 7)])
+            .await
+            .unwrap();
+        db.set_path("key2.versions[0]", 10).await.unwrap();
+        db.update()
+            .await
+            .get("key4")
+            .unwrap()
+            .push(5)
+            .unwrap()
+            .up(1)
+            .unwrap()
+            .remove("key7")
+            .unwrap();
+        // changes made through a guard are queued until they are flushed
+        let lines = |journal: &Path| std::fs::read_to_string(journal).unwrap().lines().count();
+        assert_eq!(lines(&journal), 8);
+        db.flush_journal().await.unwrap();
+        assert_eq!(lines(&journal), 10);
+        db.transaction(|tree| {
+            tree.insert("key8", 8)?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        let expected = db.data().await.inner();
+        // simulate a crash: the last operations were never written
+        drop(db);
+
+        let mut recovered = NanoDB::open(&path).unwrap();
+        assert_eq!(
+            recovered.data().await.inner(),
+            json!({"key1": "value1", "key2": {"versions": [1]}})
+        );
+        recovered.enable_journal(&journal);
+        assert_eq!(recovered.replay_journal().await.unwrap(), 11);
+        assert_eq!(recovered.data().await.inner(), expected);
+        assert_eq!(
+            expected,
+            json!({
+                "key2": {"versions": [10, 2]},
+                "key3": "new",
+                "key4": [4, 5],
+                "key5": {"a": 1},
+                "key6": 6,
+                "key8": 8
+            })
+        );
+
+        // a torn last line is ignored
+        let mut contents = std::fs::read_to_string(&journal).unwrap();
+        contents.push_str(r#"{"op":"insert","ke"#);
+        std::fs::write(&journal, contents).unwrap();
+        let mut recovered = NanoDB::open(&path).unwrap();
+        recovered.enable_journal(&journal);
+        assert_eq!(recovered.replay_journal().await.unwrap(), 11);
+        assert_eq!(recovered.data().await.inner(), expected);
+
+        recovered.write().await.unwrap();
+        assert_eq!(recovered.replay_journal().await.unwrap(), 0);
+        assert_eq!(NanoDB::open(&path).unwrap().data().await.inner(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_journal_replay_failure() {
+        let dir = tempdir().unwrap();
+        let journal = dir.path().join("data.journal");
+        let mut db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+        db.enable_journal(&journal);
+
+        let insert = r#"{"op":"insert","key":"other","value":1}"#;
+        let failing = r#"{"op":"set_path","path":["missing","x"],"value":1}"#;
+        std::fs::write(&journal, format!("{}\n\n{}\n{}\n", insert, failing, insert)).unwrap();
+        assert!(matches!(
+            db.replay_journal().await.unwrap_err(),
+            NanoDBError::JournalReplayFailed { line: 3, source } if matches!(*source, NanoDBError::InvalidJSONPath)
+        ));
+        assert_eq!(db.data().await.inner(), json!({"key": "value"}));
+        assert!(!db.dirty.load(Ordering::Acquire));
+
+        // a malformed line is only tolerated at the end of the journal
+        std::fs::write(&journal, format!("{}\n{{\"op\n{}\n", insert, insert)).unwrap();
+        assert!(matches!(
+            db.replay_journal().await.unwrap_err(),
+            NanoDBError::JournalReplayFailed { line: 2, .. }
+        ));
+        assert_eq!(db.data().await.inner(), json!({"key": "value"}));
+    }
+
+    #[tokio::test]
+    async fn test_journal_append_failure() {
+        let dir = tempdir().unwrap();
+        let mut db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
+        // the journal cannot be created since its directory does not exist
+        db.enable_journal(dir.path().join("missing").join("data.journal"));
+
+        assert!(db.insert("key", "changed").await.is_err());
+        assert!(db.clear().await.is_err());
+        assert!(db
+            .transaction(|tree| {
+                tree.insert("other", 1)?;
+                Ok(())
+            })
+            .await
+            .is_err());
+        assert_eq!(db.data().await.inner(), json!({"key": "value"}));
+
+        // guard changes are applied right away and stay queued until the journal can be written
+        db.update().await.insert("other", 1).unwrap();
+        assert!(db.flush_journal().await.is_err());
+        let journal = dir.path().join("data.journal");
+        db.enable_journal(&journal);
+        db.flush_journal().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&journal).unwrap().lines().count(),
+            1
+        );
+        db.flush_journal().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&journal).unwrap().lines().count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_path() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let db = NanoDB::open(&path).unwrap();
+        assert_eq!(db.path(), Some(path.as_path()));
+
+        assert_eq!(NanoDB::new_in_memory(json!({})).path(), None);
+    }
+
+    #[tokio::test]
+    async fn test_from_value() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let mut db = NanoDB::from_value(
+            &path,
+            json!({"key3": {"name": "NanoDB", "versions": [1.0, 2.0, 3.0]}}),
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            db.get_path("key3.versions[1]").await.unwrap().inner(),
+            json!(2.0)
+        );
+
+        db.write().await.unwrap();
+        assert_eq!(
+            NanoDB::open(&path).unwrap().data().await.inner(),
+            db.data().await.inner()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch() {
+        let mut db = NanoDB::new_from_temp(
+            r#"{"key1": "value1", "key3": {"name": "NanoDB", "versions": [1.0, 2.0, 3.0]}}"#,
+        )
+        .unwrap();
+
+        db.apply_patch(&json!([
+            {"op": "test", "path": "/key3/name", "value": "NanoDB"},
+            {"op": "add", "path": "/key3/versions/1", "value": 1.5},
+            {"op": "add", "path": "/key3/versions/-", "value": 4.0},
+            {"op": "add", "path": "/key2", "value": {"a/b": 1}},
+            {"op": "remove", "path": "/key3/versions/0"},
+            {"op": "replace", "path": "/key1", "value": "replaced"},
+            {"op": "copy", "from": "/key2/a~1b", "path": "/key4"},
+            {"op": "move", "from": "/key3/name", "path": "/name"}
+        ]))
+        .await
+        .unwrap();
+        let expected = json!({
+            "key1": "replaced",
+            "key2": {"a/b": 1},
+            "key3": {"versions": [1.5, 2.0, 3.0, 4.0]},
+            "key4": 1,
+            "name": "NanoDB"
+        });
+        assert_eq!(db.data().await.inner(), expected);
+
+        // a failing operation leaves the data unchanged, even if earlier operations succeeded
+        let failures = [
+            (
+                json!({"op": "test", "path": "/key1", "value": "value1"}),
+                "test",
+                "/key1",
+            ),
+            (json!({"op": "remove", "path": "/key0"}), "remove", "/key0"),
+            (
+                json!({"op": "replace", "path": "/key0", "value": 1}),
+                "replace",
+                "/key0",
+            ),
+            (
+                json!({"op": "add", "path": "/key3/versions/9", "value": 1}),
+                "add",
+                "/key3/versions/9",
+            ),
+            (
+                json!({"op": "move", "from": "/key3", "path": "/key3/inner"}),
+                "move",
+                "/key3/inner",
+            ),
+            (
+                json!({"op": "copy", "from": "/key0", "path": "/key5"}),
+                "copy",
+                "/key5",
+            ),
+            (
+                json!({"op": "unknown", "path": "/key1"}),
+                "unknown",
+                "/key1",
+            ),
+        ];
+        for (operation, op, path) in failures {
+            let patch = json!([{"op": "remove", "path": "/key4"}, operation]);
+            let err = db.apply_patch(&patch).await.unwrap_err();
+            assert!(
+                matches!(&err, NanoDBError::PatchFailed { op: o, path: p } if o == op && p == path),
+                "{:?}",
+                err
+            );
+            assert_eq!(db.data().await.inner(), expected);
+        }
+        assert!(matches!(
+            db.apply_patch(&json!({"op": "remove", "path": "/key1"}))
+                .await,
+            Err(NanoDBError::PatchFailed { op, path }) if op == "<not an array>" && path.is_empty()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_merge_patch() {
+        let mut db = NanoDB::new_from_temp(
+            r#"{"key1": "value1", "key3": {"name": "NanoDB", "versions": [1.0, 2.0]}}"#,
+        )
+        .unwrap();
+
+        db.merge_patch(&json!({"key1": null, "key2": 2, "key3": {"versions": [3.0]}}))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.data().await.inner(),
+            json!({"key2": 2, "key3": {"name": "NanoDB", "versions": [3.0]}})
+        );
+
+        // a patch that is not an object replaces the data wholesale
+        db.merge_patch(&json!([1, 2])).await.unwrap();
+        assert_eq!(db.data().await.inner(), json!([1, 2]));
+        db.merge_patch(&json!("scalar")).await.unwrap();
+        assert_eq!(db.data().await.inner(), json!("scalar"));
+    }
+
+    #[tokio::test]
+    async fn test_save_as() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.json");
+        let mut db = NanoDB::new_from(&first, r#"{"key": "value"}"#).unwrap();
+
+        db.save_as(&second).await.unwrap();
+        assert_eq!(db.path(), Some(second.as_path()));
+        db.insert("key", "changed").await.unwrap();
+        db.write().await.unwrap();
+
+        let read = |path: &Path| {
+            serde_json::from_str::<Value>(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+        assert_eq!(read(&first), json!({"key": "value"}));
+        assert_eq!(read(&second), json!({"key": "changed"}));
+
+        assert!(db
+            .save_as(dir.path().join("missing/third.json"))
+            .await
+            .is_err());
+        assert_eq!(db.path(), Some(second.as_path()));
+    }
+
+    #[tokio::test]
+    async fn test_get_typed() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Info {
+            name: String,
+            versions: Vec<f64>,
+        }
+
+        let db = NanoDB::new_from_temp(
+            r#"{"key2": 42, "key3": {"name": "NanoDB", "versions": [1.0, 2.0]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(db.get_typed::<i64>("key2").await.unwrap(), 42);
+        assert_eq!(
+            db.get_typed::<Info>("key3").await.unwrap(),
+            Info {
+                name: "NanoDB".to_string(),
+                versions: vec![1.0, 2.0],
+            }
         );
+        assert!(matches!(
+            db.get_typed::<i64>("key0").await,
+            Err(NanoDBError::KeyNotFound(_))
+        ));
+        assert!(matches!(
+            db.get_typed::<String>("key3").await,
+            Err(NanoDBError::TypeMismatch { actual, .. }) if actual == "object"
+        ));
     }
 
     #[tokio::test]
     async fn test_get() {
-        let db = NanoDB::new_from("/path/to/file.json", r#"{"key": "value"}"#).unwrap();
+        let db = NanoDB::new_from_temp(r#"{"key": "value"}"#).unwrap();
         let result = db.data().await.get("key").unwrap();
         assert_eq!(result.inner(), json!("value"));
     }
 
+    #[tokio::test]
+    async fn test_get_path_and_set_path() {
+        let mut db =
+            NanoDB::new_from_temp(r#"{"key3": {"name": "NanoDB", "versions": [1.0, 2.0, 3.0]}}"#)
+                .unwrap();
+
+        let versions = db.get_path("key3.versions").await.unwrap();
+        assert_eq!(versions.inner(), json!([1.0, 2.0, 3.0]));
+        assert_eq!(versions.path_string(), "key3.versions");
+
+        db.set_path("key3.versions[1]", 2.5).await.unwrap();
+        assert_eq!(
+            db.get_path("key3.versions[1]").await.unwrap().inner(),
+            json!(2.5)
+        );
+        db.set_path("key3.language", "Rust").await.unwrap();
+        assert_eq!(
+            db.get_path("key3.language").await.unwrap().inner(),
+            json!("Rust")
+        );
+
+        // missing intermediate segment
+        assert!(matches!(
+            db.set_path("key0.versions", 1).await.unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+        // out of bounds
+        assert!(matches!(
+            db.set_path("key3.versions[3]", 4.0).await.unwrap_err(),
+            NanoDBError::IndexOutOfBounds(3)
+        ));
+    }
+
     #[tokio::test]
     async fn test_insert_tree() {
-        let mut db = NanoDB::new_from(
-            "/path/to/file.json",
-            r#"{"key": {"nested_key": "nested_value"}}"#,
-        )
-        .unwrap();
+        let mut db = NanoDB::new_from_temp(r#"{"key": {"nested_key": "nested_value"}}"#).unwrap();
         let mut tree = db.data().await.get("key").unwrap();
         tree.insert("nested_key_2", "nested_value_2").unwrap();
         db.insert_tree(tree).await.unwrap();
@@ -289,12 +2756,25 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_tree_remove() {
-        let mut db = NanoDB::new_from(
-            "/path/to/file.json",
-            r#"{"key": {"nested_key": "nested_value"}}"#,
+    async fn test_merge_deep() {
+        let mut db = NanoDB::new_from_temp(
+            r#"{"config": {"db": {"host": "localhost", "port": 5432}, "debug": false}}"#,
         )
         .unwrap();
+        let partial = Tree::new(
+            json!({"db": {"port": 6543}}),
+            vec![PathStep::Key("config".to_string())],
+        );
+        db.merge_deep(partial).await.unwrap();
+        assert_eq!(
+            db.data().await.get("config").unwrap().inner(),
+            json!({"db": {"host": "localhost", "port": 6543}, "debug": false})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tree_remove() {
+        let mut db = NanoDB::new_from_temp(r#"{"key": {"nested_key": "nested_value"}}"#).unwrap();
         let mut tree = db.data().await.get("key").unwrap();
         tree.remove("nested_key").unwrap();
         db.insert_tree(tree).await.unwrap();
@@ -2,13 +2,16 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::NanoDBError;
 
+use super::tree_helper;
+
 #[derive(Debug, Clone)]
 pub struct Tree {
     inner: serde_json::Value,
     path: Vec<PathStep>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum PathStep {
     Key(String),
     Index(usize),
@@ -33,6 +36,66 @@ pub enum TreeType {
     Object,
 }
 
+impl TreeType {
+    /// Returns the type of a JSON value.
+    pub(crate) fn of(value: &serde_json::Value) -> TreeType {
+        match value {
+            serde_json::Value::Null => TreeType::Null,
+            serde_json::Value::Bool(_) => TreeType::Bool,
+            serde_json::Value::Number(_) => TreeType::Number,
+            serde_json::Value::String(_) => TreeType::String,
+            serde_json::Value::Array(_) => TreeType::Array,
+            serde_json::Value::Object(_) => TreeType::Object,
+        }
+    }
+
+    /// Returns the lowercase name of the type, e.g. `"object"`.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - One of `"null"`, `"bool"`, `"number"`, `"string"`, `"array"` or `"object"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TreeType::Null => "null",
+            TreeType::Bool => "bool",
+            TreeType::Number => "number",
+            TreeType::String => "string",
+            TreeType::Array => "array",
+            TreeType::Object => "object",
+        }
+    }
+}
+
+impl std::fmt::Display for TreeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Defines how arrays are combined when two trees are merged recursively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// The elements of the source array are appended to the target array.
+    #[default]
+    Concat,
+    /// The source array replaces the target array.
+    Replace,
+}
+
+/// A single difference between two trees, see `Tree::diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffKind {
+    /// The value only exists in the other tree.
+    Added(serde_json::Value),
+    /// The value only exists in this tree.
+    Removed(serde_json::Value),
+    /// The value exists in both trees but differs.
+    Changed {
+        from: serde_json::Value,
+        to: serde_json::Value,
+    },
+}
+
 // impl std::fmt::Display for Tree
 impl std::fmt::Display for Tree {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -40,6 +103,127 @@ impl std::fmt::Display for Tree {
     }
 }
 
+/// Indexes into the inner JSON object of the tree, e.g. `tree["key2"]["inner_key1"]`.
+///
+/// `Index` has to return a reference, so the result is a borrow of the inner `serde_json::Value`
+/// rather than a new Tree. Only the first step panics on a miss: chained indexing after it follows
+/// `serde_json::Value`'s rules, so `tree["key2"]["missing"]` returns `Null` instead of panicking.
+/// Use `get` or `get_path` to obtain a Tree that keeps track of its path and reports misses as errors.
+///
+/// # Panics
+///
+/// Panics if the inner value is not an object or `key` does not exist.
+impl std::ops::Index<&str> for Tree {
+    type Output = serde_json::Value;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        match self.inner.get(key) {
+            Some(value) => value,
+            None => panic!(
+                "key '{}' not found in the tree at '{}'",
+                key,
+                self.path_string()
+            ),
+        }
+    }
+}
+
+/// Indexes into the inner JSON array of the tree, e.g. `tree["key3"][0]`.
+///
+/// As with `Index<&str>`, the result is a borrow of the inner `serde_json::Value`, so a miss in a chained
+/// index such as `tree["key3"][5]` returns `Null` instead of panicking.
+/// Use `at` to obtain a Tree that keeps track of its path.
+///
+/// # Panics
+///
+/// Panics if the inner value is not an array or `index` is out of bounds.
+impl std::ops::Index<usize> for Tree {
+    type Output = serde_json::Value;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.inner.get(index) {
+            Some(value) => value,
+            None => panic!(
+                "index {} not found in the tree at '{}'",
+                index,
+                self.path_string()
+            ),
+        }
+    }
+}
+
+/// Two trees are equal if their inner values are equal. The path is not taken into account.
+impl PartialEq for Tree {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+/// A tree equals a JSON value if its inner value does. The path is not taken into account.
+impl PartialEq<serde_json::Value> for Tree {
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        &self.inner == other
+    }
+}
+
+/// Wraps a JSON value into a Tree with an empty path, see `Tree::from_value`.
+impl From<serde_json::Value> for Tree {
+    fn from(value: serde_json::Value) -> Self {
+        Tree::from_value(value)
+    }
+}
+
+/// Serializes only the inner value of the tree; the path is not part of the output.
+impl Serialize for Tree {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inner.serialize(serializer)
+    }
+}
+
+/// Deserializes any JSON value into a Tree with an empty path.
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_json::Value::deserialize(deserializer).map(Tree::from_value)
+    }
+}
+
+/// Iterates over the elements of the inner JSON array of the tree.
+///
+/// Each element is yielded as a Tree whose path is extended by its index.
+/// If the inner value is not an array, the iterator yields nothing.
+impl IntoIterator for Tree {
+    type Item = Tree;
+    type IntoIter = std::vec::IntoIter<Tree>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let arr = match self.inner {
+            serde_json::Value::Array(arr) => arr,
+            _ => vec![],
+        };
+        arr.into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let mut new_path = self.path.clone();
+                new_path.push(PathStep::Index(index));
+                Tree::new(value, new_path)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+/// Iterates over clones of the elements of the inner JSON array of the tree.
+///
+/// See `IntoIterator for Tree`.
+impl IntoIterator for &Tree {
+    type Item = Tree;
+    type IntoIter = std::vec::IntoIter<Tree>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone().into_iter()
+    }
+}
+
 impl Tree {
     /// Creates a new Tree instance with the given value and path.
     ///
@@ -55,8 +239,50 @@ impl Tree {
         Tree { inner: value, path }
     }
 
+    /// Creates a new Tree instance from an arbitrary JSON value, with an empty path.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to initialize the Tree with.
+    ///
+    /// # Returns
+    ///
+    /// * `Tree` - A new Tree instance with `value` as its inner value, pointing to the root.
+    pub fn from_value(value: serde_json::Value) -> Self {
+        Tree::new(value, vec![])
+    }
+
+    /// Creates a new Tree instance from an arbitrary JSON value, located at the given path.
+    ///
+    /// This allows building a tree for a known location (e.g. to pass to `merge_from` or
+    /// `NanoDB::insert_tree`) without reading the intermediate nodes first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to initialize the Tree with.
+    /// * `path` - The path of the value, starting at the root.
+    ///
+    /// # Returns
+    ///
+    /// * `Tree` - A new Tree instance with `value` as its inner value and `path` as its path.
+    pub fn at_path(value: serde_json::Value, path: Vec<PathStep>) -> Self {
+        Tree::new(value, path)
+    }
+
+    /// Creates a new Tree instance holding an explicit JSON `null`, with an empty path.
+    ///
+    /// # Returns
+    ///
+    /// * `Tree` - A new Tree instance with `Value::Null` as its inner value, pointing to the root.
+    pub fn null() -> Self {
+        Tree::from_value(serde_json::Value::Null)
+    }
+
     /// Retrieves the value associated with a given key in the inner JSON object of the tree.
     ///
+    /// A key that is present but set to `null` is not an error: the returned tree wraps `Value::Null`
+    /// (see `is_null`). Only a key that is absent yields `KeyNotFound`.
+    ///
     /// # Arguments
     ///
     /// * `key` - The key to retrieve the value for.
@@ -83,6 +309,54 @@ impl Tree {
         }
     }
 
+    /// Retrieves the value associated with a given key in the inner JSON object of the tree, if present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Tree))` - A new Tree object that represents the value associated with `key`.
+    /// * `Ok(None)` - If `key` does not exist in the JSON object.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn get_optional(&self, key: &str) -> Result<Option<Tree>, NanoDBError> {
+        match self.get(key) {
+            Ok(tree) => Ok(Some(tree)),
+            Err(NanoDBError::KeyNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Retrieves the values associated with several keys in the inner JSON object of the tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys to retrieve the values for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Tree>)` - The Tree objects for the values of `keys`, in the same order.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    /// * `Err(NanoDBError::KeyNotFound(key))` - For the first key of `keys` that does not exist in the JSON object.
+    pub fn get_many(&self, keys: &[&str]) -> Result<Vec<Tree>, NanoDBError> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
+    /// Retrieves the values associated with several keys in the inner JSON object of the tree, if present.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys to retrieve the values for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Option<Tree>>)` - For each key of `keys` in the same order, its Tree object or `None` if it does not exist.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn get_many_optional(&self, keys: &[&str]) -> Result<Vec<Option<Tree>>, NanoDBError> {
+        keys.iter().map(|key| self.get_optional(key)).collect()
+    }
+
     /// Retrieves the value at a given index in the inner JSON array of the tree.
     ///
     /// # Arguments
@@ -97,9 +371,7 @@ impl Tree {
     pub fn at(&self, index: usize) -> Result<Tree, NanoDBError> {
         match &self.inner {
             serde_json::Value::Array(arr) => {
-                let value = arr
-                    .get(index)
-                    .ok_or_else(|| NanoDBError::IndexOutOfBounds(index))?;
+                let value = arr.get(index).ok_or(NanoDBError::IndexOutOfBounds(index))?;
                 let mut new_path: Vec<PathStep> = self.path.clone();
                 new_path.push(PathStep::Index(index));
                 Ok(Tree {
@@ -111,6 +383,135 @@ impl Tree {
         }
     }
 
+    /// Retrieves the first element of the inner JSON array of the tree.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Tree))` - A new Tree object that represents the first element, with index `0` in its path.
+    /// * `Ok(None)` - If the array is empty.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn first(&self) -> Result<Option<Tree>, NanoDBError> {
+        match &self.inner {
+            serde_json::Value::Array(arr) if arr.is_empty() => Ok(None),
+            _ => self.at(0).map(Some),
+        }
+    }
+
+    /// Retrieves the last element of the inner JSON array of the tree.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Tree))` - A new Tree object that represents the last element, with its index in its path.
+    /// * `Ok(None)` - If the array is empty.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn last(&self) -> Result<Option<Tree>, NanoDBError> {
+        match &self.inner {
+            serde_json::Value::Array(arr) if arr.is_empty() => Ok(None),
+            serde_json::Value::Array(arr) => self.at(arr.len() - 1).map(Some),
+            _ => Err(NanoDBError::NotAnArray(self.path_string())),
+        }
+    }
+
+    /// Retrieves the elements in the half-open range `[start, end)` of the inner JSON array of the tree.
+    ///
+    /// `end` is clamped to the length of the array, and an empty array is returned if `end <= start`.
+    /// The returned tree keeps the path of the original array, but the indices of its elements no longer
+    /// map back to the original array, so it should not be merged back into the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first element to include.
+    /// * `end` - The index of the first element to exclude.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object wrapping an array with the selected elements.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::IndexOutOfBounds(start))` - If `start` is greater than the length of the array.
+    pub fn slice(&self, start: usize, end: usize) -> Result<Tree, NanoDBError> {
+        let arr = self
+            .inner
+            .as_array()
+            .ok_or_else(|| NanoDBError::NotAnArray(self.path_string()))?;
+        if start > arr.len() {
+            return Err(NanoDBError::IndexOutOfBounds(start));
+        }
+        let end = end.clamp(start, arr.len());
+        Ok(Tree::new(
+            serde_json::Value::Array(arr[start..end].to_vec()),
+            self.path.clone(),
+        ))
+    }
+
+    /// Retrieves the value at a dotted path (e.g. `"key3.versions[0]"`) relative to the tree.
+    ///
+    /// Keys are separated by dots and array indices are written in brackets. Keys containing
+    /// dots or brackets cannot be addressed this way; use `get` and `at` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path to retrieve the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the value at `path`.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If `path` cannot be parsed.
+    /// * `Err(NanoDBError::PathNotFound)` - If a key along the path does not exist. `path` is the location
+    ///   where the key was expected, including the path of the tree itself, and `missing` is the key.
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index along the path is out of bounds.
+    /// * `Err(NanoDBError::NotAnObject)` - If a key is used on a value that is not an object.
+    /// * `Err(NanoDBError::NotAnArray)` - If an index is used on a value that is not an array.
+    pub fn get_path(&self, path: &str) -> Result<Tree, NanoDBError> {
+        let steps = tree_helper::parse_path(path)?;
+        let value = tree_helper::resolve(&self.inner, &self.path, &steps)?;
+        let mut new_path: Vec<PathStep> = self.path.clone();
+        new_path.extend(steps);
+        Ok(Tree::new(value.clone(), new_path))
+    }
+
+    /// Retrieves the value at a JSON Pointer (RFC 6901) such as `"/key3/versions/0"` relative to the tree.
+    ///
+    /// `~1` and `~0` in a reference token are unescaped to `/` and `~` respectively. The path of the
+    /// returned Tree reflects the resolved location, so it can be merged back later.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - The JSON Pointer to retrieve the value for. The empty string refers to the tree itself.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the value at `pointer`.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If `pointer` is malformed or a token does not resolve.
+    pub fn pointer(&self, pointer: &str) -> Result<Tree, NanoDBError> {
+        let tokens = tree_helper::parse_pointer(pointer)?;
+        let (value, steps) = tree_helper::resolve_pointer(&self.inner, &tokens)?;
+        let mut new_path: Vec<PathStep> = self.path.clone();
+        new_path.extend(steps);
+        Ok(Tree::new(value.clone(), new_path))
+    }
+
+    /// Serializes the inner JSON value of the Tree instance into a compact JSON string.
+    ///
+    /// Unlike the `Display` implementation, the path of the tree is not included.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The inner value as compact JSON.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the inner value.
+    pub fn to_json_string(&self) -> Result<String, NanoDBError> {
+        Ok(serde_json::to_string(&self.inner)?)
+    }
+
+    /// Serializes the inner JSON value of the Tree instance into a pretty-printed JSON string.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The inner value as pretty-printed JSON.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the inner value.
+    pub fn to_json_string_pretty(&self) -> Result<String, NanoDBError> {
+        Ok(serde_json::to_string_pretty(&self.inner)?)
+    }
+
     /// Returns a clone of the inner JSON value of the Tree instance.
     ///
     /// # Returns
@@ -129,17 +530,32 @@ impl Tree {
         self.path.clone()
     }
 
+    /// Returns the steps of the path of the Tree instance, starting at the root.
+    ///
+    /// # Returns
+    ///
+    /// * `&[PathStep]` - A borrow of the path steps.
+    pub fn path_steps(&self) -> &[PathStep] {
+        &self.path
+    }
+
+    /// Checks if the tree points to the root of the JSON data, i.e. if its path is empty.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - If the path of the tree is empty.
+    /// * `false` - Otherwise.
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
     /// Returns the path of the Tree instance as a dot-separated string.
     ///
     /// # Returns
     ///
     /// * `String` - The path as a dot-separated string.
     pub fn path_string(&self) -> String {
-        self.path
-            .iter()
-            .map(|p| p.to_string())
-            .collect::<Vec<String>>()
-            .join(".")
+        tree_helper::path_to_string(&self.path)
     }
 
     /// Converts the inner JSON value of the Tree instance into a specified type.
@@ -151,9 +567,85 @@ impl Tree {
     /// # Returns
     ///
     /// * `Ok(T)` - The JSON value converted into the specified type.
-    /// * `Err(NanoDBError)` - If there was an error during the conversion.
+    /// * `Err(NanoDBError::TypeMismatch)` - If the JSON value cannot be converted. The error reports the name of `T`,
+    ///   the type of the JSON value (see `TreeType::as_str`) and serde's message.
     pub fn into<T: for<'de> Deserialize<'de>>(self) -> Result<T, NanoDBError> {
-        serde_json::from_value(self.inner).map_err(|e| NanoDBError::TypeMismatch(e.to_string()))
+        let actual = self.type_name();
+        serde_json::from_value(self.inner).map_err(|e| NanoDBError::TypeMismatch {
+            expected: std::any::type_name::<T>().to_string(),
+            actual: actual.to_string(),
+            detail: e.to_string(),
+        })
+    }
+
+    /// Returns the inner value of the tree as a string slice, if it is a string.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` - If the inner value is a string.
+    /// * `None` - Otherwise.
+    pub fn as_str(&self) -> Option<&str> {
+        self.inner.as_str()
+    }
+
+    /// Returns the inner value of the tree as an `i64`, if it is an integer that fits.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(i64)` - If the inner value is a number representable as `i64`.
+    /// * `None` - Otherwise.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.inner.as_i64()
+    }
+
+    /// Returns the inner value of the tree as an `f64`, if it is a number.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(f64)` - If the inner value is a number.
+    /// * `None` - Otherwise.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.inner.as_f64()
+    }
+
+    /// Checks if the inner value of the tree is JSON `null`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - If the inner value is `null`.
+    /// * `false` - Otherwise.
+    pub fn is_null(&self) -> bool {
+        self.inner.is_null()
+    }
+
+    /// Returns the inner value of the tree as a `bool`, if it is a boolean.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(bool)` - If the inner value is a boolean.
+    /// * `None` - Otherwise.
+    pub fn as_bool(&self) -> Option<bool> {
+        self.inner.as_bool()
+    }
+
+    /// Returns a borrow of the inner JSON object of the tree, if it is an object.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Map<String, Value>)` - If the inner value is an object.
+    /// * `None` - Otherwise.
+    pub fn as_object(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.inner.as_object()
+    }
+
+    /// Returns a borrow of the inner JSON array of the tree, if it is an array.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Vec<Value>)` - If the inner value is an array.
+    /// * `None` - Otherwise.
+    pub fn as_array(&self) -> Option<&Vec<serde_json::Value>> {
+        self.inner.as_array()
     }
 
     /// Returns the type of the inner value of the tree.
@@ -162,14 +654,16 @@ impl Tree {
     ///
     /// * `TreeType` - The type of the inner value of the tree. This can be one of `Null`, `Bool`, `Number`, `String`, `Array`, or `Object`.
     pub fn tree_type(&self) -> TreeType {
-        match &self.inner {
-            serde_json::Value::Null => TreeType::Null,
-            serde_json::Value::Bool(_) => TreeType::Bool,
-            serde_json::Value::Number(_) => TreeType::Number,
-            serde_json::Value::String(_) => TreeType::String,
-            serde_json::Value::Array(_) => TreeType::Array,
-            serde_json::Value::Object(_) => TreeType::Object,
-        }
+        TreeType::of(&self.inner)
+    }
+
+    /// Returns the name of the type of the inner value of the tree, see `TreeType::as_str`.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - The name of the type of the inner value, e.g. `"array"`.
+    pub fn type_name(&self) -> &'static str {
+        self.tree_type().as_str()
     }
 
     /// Inserts a key-value pair into the inner JSON object of the Tree instance.
@@ -197,40 +691,157 @@ impl Tree {
         Ok(self.clone())
     }
 
-    /// Removes a key-value pair from the inner JSON object of the Tree instance.
+    /// Replaces the inner value of the Tree instance, keeping its path.
+    ///
+    /// Unlike `insert`, the inner value does not have to be an object.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to remove the value for.
+    /// * `value` - The new value. This value must implement the `Serialize` trait.
     ///
     /// # Returns
     ///
-    /// * `Ok(Tree)` - A clone of the Tree instance after the removal.
-    /// * `Err(NanoDBError)` - If the inner JSON value is not an object or the key does not exist.
-    pub fn remove(&mut self, key: &str) -> Result<Tree, NanoDBError> {
-        // check if the inner value is an object
-        if !self.inner.is_object() {
-            return Err(NanoDBError::NotAnObject(key.to_string()));
-        }
-
-        // check if key exists
-        if !self.inner.as_object().unwrap().contains_key(key) {
-            return Err(NanoDBError::KeyNotFound(key.to_string()));
-        }
-
-        self.inner.as_object_mut().unwrap().remove(key);
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the replacement.
+    /// * `Err(serde_json::Error)` - If there was an error serializing `value`.
+    pub fn set<T: Serialize>(&mut self, value: T) -> Result<Tree, NanoDBError> {
+        self.inner = serde_json::to_value(value)?;
         Ok(self.clone())
     }
 
-    /// Removes an element at a specific index from the array stored in the `inner` field of the `Tree` instance.
+    /// Modifies the value at a dotted path (e.g. `"key3.versions[0]"`) relative to the tree in place.
+    ///
+    /// The closure receives a mutable reference to the value, so no subtree has to be cloned and merged back.
     ///
     /// # Arguments
     ///
-    /// * `index` - The index at which to remove the element.
+    /// * `path` - The dotted path of the value to modify. The grammar is the same as for `get_path`.
+    /// * `f` - The closure to call with the value at `path`.
     ///
     /// # Returns
     ///
-    /// * `Ok(Tree)` - A clone of the `Tree` instance after the removal.
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If `path` cannot be parsed or does not resolve.
+    pub fn modify<F>(&mut self, path: &str, f: F) -> Result<(), NanoDBError>
+    where
+        F: FnOnce(&mut serde_json::Value),
+    {
+        let steps = tree_helper::parse_path(path)?;
+        let value = tree_helper::resolve_mut(&mut self.inner, &steps)
+            .map_err(|_| NanoDBError::InvalidJSONPath)?;
+        f(value);
+        Ok(())
+    }
+
+    /// Retrieves the value associated with a given key, inserting a default value first if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve the value for.
+    /// * `default` - A function producing the value to insert if `key` does not exist. The value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the (existing or inserted) value associated with `key`.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    /// * `Err(NanoDBError::SerializationError)` - If there was an error serializing the default value.
+    pub fn get_or_insert_with<T, F>(&mut self, key: &str, default: F) -> Result<Tree, NanoDBError>
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        if !self.contains_key(key)? {
+            self.insert(key, default())?;
+        }
+        self.get(key)
+    }
+
+    /// Removes a key-value pair from the inner JSON object of the Tree instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A clone of the Tree instance after the removal.
+    /// * `Err(NanoDBError)` - If the inner JSON value is not an object or the key does not exist.
+    pub fn remove(&mut self, key: &str) -> Result<Tree, NanoDBError> {
+        // check if the inner value is an object
+        if !self.inner.is_object() {
+            return Err(NanoDBError::NotAnObject(key.to_string()));
+        }
+
+        // check if key exists
+        if !self.inner.as_object().unwrap().contains_key(key) {
+            return Err(NanoDBError::KeyNotFound(key.to_string()));
+        }
+
+        self.inner.as_object_mut().unwrap().remove(key);
+        Ok(self.clone())
+    }
+
+    /// Removes a key-value pair from the inner JSON object of the Tree instance and returns the removed value.
+    ///
+    /// Unlike `remove`, which returns a clone of the tree after the removal, this returns the value that was
+    /// stored under `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the removed value, with the path it had before the removal.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    /// * `Err(NanoDBError::KeyNotFound(key))` - If `key` does not exist in the JSON object.
+    pub fn take(&mut self, key: &str) -> Result<Tree, NanoDBError> {
+        let map = self
+            .inner
+            .as_object_mut()
+            .ok_or_else(|| NanoDBError::NotAnObject(key.to_string()))?;
+        let value = map
+            .remove(key)
+            .ok_or_else(|| NanoDBError::KeyNotFound(key.to_string()))?;
+        let mut new_path: Vec<PathStep> = self.path.clone();
+        new_path.push(PathStep::Key(key.to_string()));
+        Ok(Tree::new(value, new_path))
+    }
+
+    /// Renames a key of the inner JSON object of the Tree instance, keeping its value.
+    ///
+    /// If `to` already exists, its value is overwritten by the value of `from`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The key to rename.
+    /// * `to` - The new name of the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A clone of the Tree instance after the renaming.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    /// * `Err(NanoDBError::KeyNotFound)` - If `from` does not exist in the JSON object.
+    pub fn rename_key(&mut self, from: &str, to: &str) -> Result<Tree, NanoDBError> {
+        let map = self
+            .inner
+            .as_object_mut()
+            .ok_or_else(|| NanoDBError::NotAnObject(from.to_string()))?;
+        let value = map
+            .remove(from)
+            .ok_or_else(|| NanoDBError::KeyNotFound(from.to_string()))?;
+        map.insert(to.to_string(), value);
+        Ok(self.clone())
+    }
+
+    /// Removes an element at a specific index from the array stored in the `inner` field of the `Tree` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index at which to remove the element.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A clone of the `Tree` instance after the removal.
     /// * `Err(NanoDBError)` - If the `inner` field is not an array or the index is out of bounds.
     /// # Errors
     ///
@@ -250,6 +861,63 @@ impl Tree {
         Ok(self.clone())
     }
 
+    /// Removes an element at a specific index from the inner array of the tree and returns the removed element.
+    ///
+    /// Unlike `remove_at`, which returns a clone of the tree after the removal, this returns the element that was
+    /// stored at `index`.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index at which to remove the element.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the removed element, with the path it had before the removal.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::IndexOutOfBounds(index))` - If `index` is out of bounds of the array.
+    pub fn take_at(&mut self, index: usize) -> Result<Tree, NanoDBError> {
+        let path = self.path_string();
+        let arr = self
+            .inner
+            .as_array_mut()
+            .ok_or(NanoDBError::NotAnArray(path))?;
+        if index >= arr.len() {
+            return Err(NanoDBError::IndexOutOfBounds(index));
+        }
+        let value = arr.remove(index);
+        let mut new_path: Vec<PathStep> = self.path.clone();
+        new_path.push(PathStep::Index(index));
+        Ok(Tree::new(value, new_path))
+    }
+
+    /// Removes the first element equal to the given value from the array stored in the `inner` field of the `Tree` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to remove. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A clone of the `Tree` instance after the removal.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::ElementNotFound)` - If no element of the array is equal to `value`.
+    pub fn remove_elem<T: Serialize>(&mut self, value: T) -> Result<Tree, NanoDBError> {
+        let value = serde_json::to_value(value)?;
+        let path = self.path_string();
+
+        let arr = self
+            .inner
+            .as_array_mut()
+            .ok_or_else(|| NanoDBError::NotAnArray(path.clone()))?;
+        let index = arr
+            .iter()
+            .position(|v| *v == value)
+            .ok_or(NanoDBError::ElementNotFound(path))?;
+        arr.remove(index);
+
+        Ok(self.clone())
+    }
+
     /// Merges a Tree (other) into the JSON data of the NanoDB instance
     /// It does so by respecting the path of the other Tree instance.
     ///
@@ -265,37 +933,94 @@ impl Tree {
     /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
     pub fn merge_from(&mut self, other: Tree) -> Result<&mut Self, NanoDBError> {
         let path = other.path();
-        let mut current = &mut self.inner;
-
-        for p in path {
-            match p {
-                PathStep::Key(key) => {
-                    if current.is_object() {
-                        let obj = current.as_object_mut().unwrap();
-                        match obj.get_mut(&key) {
-                            Some(value) => current = value,
-                            None => return Err(NanoDBError::InvalidJSONPath),
-                        }
-                    } else {
-                        return Err(NanoDBError::InvalidJSONPath);
-                    }
-                }
-                PathStep::Index(idx) => {
-                    if current.is_array() {
-                        let arr = current.as_array_mut().unwrap();
-                        current = arr.get_mut(idx).ok_or(NanoDBError::IndexOutOfBounds(idx))?;
-                    } else {
-                        return Err(NanoDBError::InvalidJSONPath);
-                    }
-                }
-            }
-        }
+        let current = tree_helper::resolve_mut(&mut self.inner, &path)?;
+        *current = other.inner;
+
+        Ok(self)
+    }
+
+    /// Merges this tree into a parent tree at the path of this tree.
+    ///
+    /// This is the inverse direction of `merge_from`: `subtree.merge_into(&mut parent)` is equivalent to
+    /// `parent.merge_from(subtree)`, which reads more naturally when writing a subtree back into the tree
+    /// it was taken from.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The Tree to merge this tree into.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the operation was successful.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If the path does not exist in the parent or if a path step is not valid for the current value (e.g., using a key on an array or an index on an object).
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
+    pub fn merge_into(self, parent: &mut Tree) -> Result<(), NanoDBError> {
+        parent.merge_from(self)?;
+        Ok(())
+    }
+
+    /// Recursively merges a Tree (other) into this tree, respecting the path of the other Tree instance.
+    ///
+    /// Unlike `merge_from`, which replaces the value at the other tree's path wholesale, objects are merged
+    /// key by key, so that merging `{"b": 2}` into `{"a": 1}` yields `{"a": 1, "b": 2}`. Arrays are
+    /// concatenated (use `merge_deep_from_with` and `ArrayMerge::Replace` to overwrite them instead).
+    /// For any other combination, including mixed types, the value of the other tree wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The Tree to merge into this tree.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The Tree instance itself after the merge. This allows for method chaining.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If the path does not exist in the JSON data or if a path step is not valid for the current value.
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
+    pub fn merge_deep_from(&mut self, other: Tree) -> Result<&mut Self, NanoDBError> {
+        self.merge_deep_from_with(other, ArrayMerge::Concat)
+    }
 
-        *current = other.inner();
+    /// Recursively merges a Tree (other) into this tree like `merge_deep_from`, using the given strategy for arrays.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The Tree to merge into this tree.
+    /// * `arrays` - Whether arrays present on both sides are concatenated or replaced.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The Tree instance itself after the merge. This allows for method chaining.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If the path does not exist in the JSON data or if a path step is not valid for the current value.
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If an index path step is out of bounds of the array.
+    pub fn merge_deep_from_with(
+        &mut self,
+        other: Tree,
+        arrays: ArrayMerge,
+    ) -> Result<&mut Self, NanoDBError> {
+        let path = other.path();
+        let current = tree_helper::resolve_mut(&mut self.inner, &path)?;
+        tree_helper::merge_values(current, other.inner, arrays);
 
         Ok(self)
     }
 
+    /// Applies a JSON Merge Patch (RFC 7386) to the inner value of the tree.
+    ///
+    /// If `patch` is an object, it is merged key by key, recursively: a `null` value removes the key, and any other
+    /// value is merged into the existing one (a non-object target is replaced by an empty object first). Any patch
+    /// that is not an object replaces the inner value wholesale.
+    ///
+    /// # Arguments
+    ///
+    /// * `patch` - The merge patch document.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the patch has been applied.
+    pub fn merge_patch(&mut self, patch: &serde_json::Value) -> Result<Tree, NanoDBError> {
+        tree_helper::merge_patch(&mut self.inner, patch);
+        Ok(self.clone())
+    }
+
     /// Pushes a value to the tree if it's an array.
     ///
     /// # Arguments
@@ -318,211 +1043,2069 @@ impl Tree {
         Ok(self.clone())
     }
 
-    /// Applies a function to each element of the inner array of the tree.
+    /// Appends multiple values to the tree if it's an array.
     ///
     /// # Arguments
     ///
-    /// * `f` - A mutable function that takes a mutable reference to a `serde_json::Value` and returns `()`.
+    /// * `values` - An iterator of values that implement the Serialize trait. Each value will be serialized to JSON and pushed to the array.
     ///
     /// # Returns
     ///
-    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the function has been applied to each element.
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the values have been pushed.
     /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
-    pub fn for_each<F>(&mut self, f: F) -> Result<Tree, NanoDBError>
+    pub fn extend<T, I>(&mut self, values: I) -> Result<Tree, NanoDBError>
     where
-        F: FnMut(&mut serde_json::Value),
+        T: Serialize,
+        I: IntoIterator<Item = T>,
     {
-        if let Some(v) = self.inner.as_array_mut() {
-            v.iter_mut().for_each(f);
-        } else {
+        if !self.inner.is_array() {
             return Err(NanoDBError::NotAnArray(self.path_string()));
         }
 
+        let values = values
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.inner.as_array_mut().unwrap().extend(values);
+
         Ok(self.clone())
     }
 
-    /// Returns the length of the inner array of the tree.
+    /// Appends the values that are not yet present to the tree if it's an array, giving set-union semantics.
+    ///
+    /// Values are compared by structural equality. The order of existing elements and of the appended values is
+    /// preserved, and duplicates within `values` are only appended once.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - An iterator of values that implement the Serialize trait.
     ///
     /// # Returns
     ///
-    /// * `Ok(usize)` - The length of the array if the inner value of the tree is an array.
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the values have been merged.
     /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
-    pub fn len(&self) -> Result<usize, NanoDBError> {
-        match &self.inner {
-            serde_json::Value::Array(arr) => Ok(arr.len()),
-            serde_json::Value::Object(obj) => Ok(obj.len()),
-            _ => Err(NanoDBError::LenNotDefined(self.path_string())),
+    pub fn merge_array_unique<T, I>(&mut self, values: I) -> Result<Tree, NanoDBError>
+    where
+        T: Serialize,
+        I: IntoIterator<Item = T>,
+    {
+        if !self.inner.is_array() {
+            return Err(NanoDBError::NotAnArray(self.path_string()));
+        }
+
+        let values = values
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        let arr = self.inner.as_array_mut().unwrap();
+        for value in values {
+            if !arr.contains(&value) {
+                arr.push(value);
+            }
         }
+
+        Ok(self.clone())
     }
 
-    /// Checks if the inner JSON array of the Tree instance is empty.
+    /// Inserts a value into the inner array of the tree at the given index, shifting all elements after it to the right.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index at which to insert the value. If `index` equals the length of the array, the value is appended.
+    /// * `value` - A value of type T that implements the Serialize trait. This value will be serialized to JSON and inserted into the array.
     ///
     /// # Returns
     ///
-    /// * `true` - If the inner JSON value empty.
-    /// * `false` - If the inner JSON value is not empty.
-    pub fn is_empty(&self) -> bool {
-        match &self.inner {
-            serde_json::Value::Array(arr) => arr.is_empty(),
-            serde_json::Value::Object(obj) => obj.is_empty(),
-            _ => false,
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the value has been inserted.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::IndexOutOfBounds(index))` - If `index` is greater than the length of the array.
+    pub fn insert_at<T: Serialize>(&mut self, index: usize, value: T) -> Result<Tree, NanoDBError> {
+        let value = serde_json::to_value(value)?;
+
+        if let Some(v) = self.inner.as_array_mut() {
+            if index > v.len() {
+                return Err(NanoDBError::IndexOutOfBounds(index));
+            }
+            v.insert(index, value);
+        } else {
+            return Err(NanoDBError::NotAnArray(self.path_string()));
         }
+
+        Ok(self.clone())
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Sorts the inner array of the tree in ascending order.
+    ///
+    /// Only arrays whose elements are all numbers or all strings can be sorted this way.
+    /// Use `sort_by` for anything else.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after sorting.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::Unsortable)` - If the array mixes types or contains values other than numbers and strings.
+    pub fn sort(&mut self) -> Result<Tree, NanoDBError> {
+        let path = self.path_string();
+        let arr = self
+            .inner
+            .as_array_mut()
+            .ok_or_else(|| NanoDBError::NotAnArray(path.clone()))?;
 
-    use crate::{error::NanoDBError, trees::tree::Tree};
-    use serde_json::{json, Value};
+        if arr.iter().all(|v| v.is_number()) {
+            arr.sort_by(|a, b| {
+                tree_helper::compare_numbers(a.as_number().unwrap(), b.as_number().unwrap())
+            });
+        } else if arr.iter().all(|v| v.is_string()) {
+            arr.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+        } else {
+            return Err(NanoDBError::Unsortable(path));
+        }
 
-    fn value() -> Value {
-        serde_json::from_str(
-            r#"{
-			"key1": "value1",
-			"key2": {
-				"inner_key1": "inner_value1",
-				"inner_key2": "inner_value2"
-			},
-			"key3": [1, 2, 3]
-		}"#,
-        )
-        .unwrap()
+        Ok(self.clone())
     }
 
-    #[tokio::test]
-    async fn test_tree_get() {
-        let tree1 = Tree::new(value(), vec![]).get("key1").unwrap();
-        assert_eq!(tree1.inner(), json!("value1"));
+    /// Sorts the inner array of the tree with a comparator function.
+    ///
+    /// # Arguments
+    ///
+    /// * `compare` - A function that takes two references to a `serde_json::Value` and returns their `Ordering`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after sorting.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn sort_by<F>(&mut self, compare: F) -> Result<Tree, NanoDBError>
+    where
+        F: FnMut(&serde_json::Value, &serde_json::Value) -> std::cmp::Ordering,
+    {
+        if let Some(v) = self.inner.as_array_mut() {
+            v.sort_by(compare);
+        } else {
+            return Err(NanoDBError::NotAnArray(self.path_string()));
+        }
 
-        let tree2 = Tree::new(value(), vec![]).get("key2").unwrap();
-        assert_eq!(
-            tree2.inner(),
-            json!({
-                "inner_key1": "inner_value1",
-                "inner_key2": "inner_value2"
-            })
-        );
+        Ok(self.clone())
+    }
 
-        // This one should return an error
+    /// Reverses the order of the elements of the inner array of the tree in place.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after reversing.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn reverse(&mut self) -> Result<Tree, NanoDBError> {
+        if let Some(v) = self.inner.as_array_mut() {
+            v.reverse();
+        } else {
+            return Err(NanoDBError::NotAnArray(self.path_string()));
+        }
+
+        Ok(self.clone())
+    }
+
+    /// Applies a function to each element of the inner array of the tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A mutable function that takes a mutable reference to a `serde_json::Value` and returns `()`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after the function has been applied to each element.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn for_each<F>(&mut self, f: F) -> Result<Tree, NanoDBError>
+    where
+        F: FnMut(&mut serde_json::Value),
+    {
+        if let Some(v) = self.inner.as_array_mut() {
+            v.iter_mut().for_each(f);
+        } else {
+            return Err(NanoDBError::NotAnArray(self.path_string()));
+        }
+
+        Ok(self.clone())
+    }
+
+    /// Maps each element of the inner array of the tree to a new value, leaving the tree itself untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A mutable function that takes a reference to a `serde_json::Value` and returns the mapped value.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object with the same path, wrapping an array of the mapped values.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn map<F>(&self, f: F) -> Result<Tree, NanoDBError>
+    where
+        F: FnMut(&serde_json::Value) -> serde_json::Value,
+    {
+        let arr = self
+            .inner
+            .as_array()
+            .ok_or_else(|| NanoDBError::NotAnArray(self.path_string()))?;
+        Ok(Tree::new(
+            serde_json::Value::Array(arr.iter().map(f).collect()),
+            self.path.clone(),
+        ))
+    }
+
+    /// Collects the value of `key` from each object in the inner array of the tree.
+    ///
+    /// Elements that are not objects or do not contain `key` are skipped; use `pluck_strict` to treat them as errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to collect the values for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object with the same path, wrapping an array of the collected values.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn pluck(&self, key: &str) -> Result<Tree, NanoDBError> {
+        let arr = self
+            .inner
+            .as_array()
+            .ok_or_else(|| NanoDBError::NotAnArray(self.path_string()))?;
+        Ok(Tree::new(
+            serde_json::Value::Array(arr.iter().filter_map(|v| v.get(key)).cloned().collect()),
+            self.path.clone(),
+        ))
+    }
+
+    /// Collects the value of `key` from each object in the inner array of the tree, failing on the first element without it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to collect the values for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object with the same path, wrapping an array of the collected values.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::NotAnObject)` - If an element of the array is not an object.
+    /// * `Err(NanoDBError::KeyNotFound(key))` - If an element of the array does not contain `key`.
+    pub fn pluck_strict(&self, key: &str) -> Result<Tree, NanoDBError> {
+        let arr = self
+            .inner
+            .as_array()
+            .ok_or_else(|| NanoDBError::NotAnArray(self.path_string()))?;
+        let values = arr
+            .iter()
+            .map(|v| {
+                v.as_object()
+                    .ok_or_else(|| NanoDBError::NotAnObject(key.to_string()))?
+                    .get(key)
+                    .cloned()
+                    .ok_or_else(|| NanoDBError::KeyNotFound(key.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Tree::new(
+            serde_json::Value::Array(values),
+            self.path.clone(),
+        ))
+    }
+
+    /// Selects the objects in the inner array of the tree whose value at `key` equals `value`.
+    ///
+    /// Elements that are not objects, do not contain `key`, or hold a different value are excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to compare.
+    /// * `value` - The value to compare against. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object with the same path, wrapping an array of the matching elements.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn query<T: Serialize>(&self, key: &str, value: T) -> Result<Tree, NanoDBError> {
+        let value = serde_json::to_value(value)?;
+        let arr = self
+            .inner
+            .as_array()
+            .ok_or_else(|| NanoDBError::NotAnArray(self.path_string()))?;
+        Ok(Tree::new(
+            serde_json::Value::Array(
+                arr.iter()
+                    .filter(|v| v.as_object().and_then(|o| o.get(key)) == Some(&value))
+                    .cloned()
+                    .collect(),
+            ),
+            self.path.clone(),
+        ))
+    }
+
+    /// Retains only the elements of the inner array of the tree for which the predicate returns `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A mutable function that takes a reference to a `serde_json::Value` and returns whether to keep it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current state of the tree after filtering.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn retain<F>(&mut self, predicate: F) -> Result<Tree, NanoDBError>
+    where
+        F: FnMut(&serde_json::Value) -> bool,
+    {
+        if let Some(v) = self.inner.as_array_mut() {
+            v.retain(predicate);
+        } else {
+            return Err(NanoDBError::NotAnArray(self.path_string()));
+        }
+
+        Ok(self.clone())
+    }
+
+    /// Returns the first element of the inner array of the tree for which the predicate returns `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A mutable function that takes a reference to a `serde_json::Value` and returns whether it matches.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Tree))` - A new Tree object that represents the first matching element. Its path ends with the element's index.
+    /// * `Ok(None)` - If no element matches.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn find<F>(&self, predicate: F) -> Result<Option<Tree>, NanoDBError>
+    where
+        F: FnMut(&serde_json::Value) -> bool,
+    {
+        match self.position(predicate)? {
+            Some(index) => self.at(index).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the index of the first element of the inner array of the tree for which the predicate returns `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A mutable function that takes a reference to a `serde_json::Value` and returns whether it matches.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(usize))` - The index of the first matching element.
+    /// * `Ok(None)` - If no element matches.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn position<F>(&self, predicate: F) -> Result<Option<usize>, NanoDBError>
+    where
+        F: FnMut(&serde_json::Value) -> bool,
+    {
+        match &self.inner {
+            serde_json::Value::Array(arr) => Ok(arr.iter().position(predicate)),
+            _ => Err(NanoDBError::NotAnArray(self.path_string())),
+        }
+    }
+
+    /// Removes all elements of the inner array or all members of the inner object of the tree.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the current, empty state of the tree.
+    /// * `Err(NanoDBError::LenNotDefined)` - If the inner value of the tree is neither an array nor an object.
+    pub fn clear(&mut self) -> Result<Tree, NanoDBError> {
+        match &mut self.inner {
+            serde_json::Value::Array(arr) => arr.clear(),
+            serde_json::Value::Object(obj) => obj.clear(),
+            _ => return Err(NanoDBError::LenNotDefined(self.path_string())),
+        }
+
+        Ok(self.clone())
+    }
+
+    /// Returns the length of the inner array of the tree.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the array if the inner value of the tree is an array.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn len(&self) -> Result<usize, NanoDBError> {
+        match &self.inner {
+            serde_json::Value::Array(arr) => Ok(arr.len()),
+            serde_json::Value::Object(obj) => Ok(obj.len()),
+            _ => Err(NanoDBError::LenNotDefined(self.path_string())),
+        }
+    }
+
+    /// Returns the maximum nesting depth of the inner value of the tree.
+    ///
+    /// Scalars have a depth of 0, a flat array or object has a depth of 1, and each level of nesting adds 1.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The nesting depth of the inner value.
+    pub fn depth(&self) -> usize {
+        tree_helper::depth(&self.inner)
+    }
+
+    /// Returns the total number of nodes in the inner value of the tree.
+    ///
+    /// Every scalar and every array or object counts as one node, including the inner value itself.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of nodes in the inner value.
+    pub fn count_nodes(&self) -> usize {
+        tree_helper::count_nodes(&self.inner)
+    }
+
+    /// Computes the structural differences from the inner value of this tree to the inner value of `other`.
+    ///
+    /// Objects are compared key by key and arrays index by index, recursively. Values of different types,
+    /// and differing scalars, are reported as `DiffKind::Changed`. Only the inner values are compared, not the paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The tree to compare against, e.g. the edited version of this tree.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, DiffKind)>` - The differences, keyed by their dotted path (e.g. `"key3.versions[1]"`) relative
+    ///   to the inner values. The path is empty if the inner values themselves differ. An empty vector means the trees are equal.
+    pub fn diff(&self, other: &Tree) -> Vec<(String, DiffKind)> {
+        let mut out = Vec::new();
+        tree_helper::diff(&self.inner, &other.inner, &mut vec![], &mut out);
+        out
+    }
+
+    /// Visits every node of the inner value of the tree depth-first, starting with the inner value itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `visit` - The closure to call for each node. It receives the path of the node relative to this
+    ///   tree (empty for the inner value itself) and the node's value.
+    pub fn walk<F>(&self, mut visit: F)
+    where
+        F: FnMut(&[PathStep], &serde_json::Value),
+    {
+        tree_helper::walk(&self.inner, &mut vec![], &mut visit);
+    }
+
+    /// Visits every node of the inner value of the tree depth-first like `walk`, allowing the closure to
+    /// rewrite nodes in place.
+    ///
+    /// A node is visited before its children, so if the closure replaces a node, the children of the new
+    /// value are visited afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `visit` - The closure to call for each node. It receives the path of the node relative to this
+    ///   tree and a mutable reference to the node's value.
+    pub fn walk_mut<F>(&mut self, mut visit: F)
+    where
+        F: FnMut(&[PathStep], &mut serde_json::Value),
+    {
+        tree_helper::walk_mut(&mut self.inner, &mut vec![], &mut visit);
+    }
+
+    /// Flattens the inner value of the tree into pairs of dotted paths and leaf values.
+    ///
+    /// Paths are relative to this tree and use the grammar of `get_path`, e.g. `key3.versions[0]`.
+    /// Leaves are scalar values as well as empty arrays and objects, so that `Tree::unflatten` can
+    /// rebuild the same structure. Keys containing dots or brackets cannot be represented unambiguously.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, serde_json::Value)>` - The leaves of the inner value in depth-first order.
+    pub fn flatten(&self) -> Vec<(String, serde_json::Value)> {
+        let mut pairs = vec![];
+        self.walk(|path, value| {
+            let is_leaf = match value {
+                serde_json::Value::Array(arr) => arr.is_empty(),
+                serde_json::Value::Object(map) => map.is_empty(),
+                _ => true,
+            };
+            if is_leaf {
+                pairs.push((tree_helper::format_path(path), value.clone()));
+            }
+        });
+        pairs
+    }
+
+    /// Builds a tree from pairs of dotted paths and values, the inverse of `flatten`.
+    ///
+    /// Paths use the grammar of `get_path`. Objects are created for key segments and arrays for
+    /// `[index]` segments; array positions that are not set are filled with `null`. The returned tree
+    /// has an empty path, and an empty object if `pairs` is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs` - The dotted paths and the values to set at them.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree with the rebuilt structure.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If a path cannot be parsed, if paths conflict (e.g. the same
+    ///   node is used both as an object and as an array), or if the same path is set twice.
+    pub fn unflatten(
+        pairs: impl IntoIterator<Item = (String, serde_json::Value)>,
+    ) -> Result<Tree, NanoDBError> {
+        let mut root = serde_json::Value::Null;
+        let mut is_empty = true;
+        for (path, value) in pairs {
+            let steps = tree_helper::parse_path(&path)?;
+            tree_helper::insert_path(&mut root, &steps, value)?;
+            is_empty = false;
+        }
+        if is_empty {
+            root = serde_json::Value::Object(Default::default());
+        }
+        Ok(Tree::from_value(root))
+    }
+
+    /// Returns the keys of the inner JSON object of the tree.
+    ///
+    /// The keys are returned in the order in which serde_json stores them, i.e. sorted
+    /// alphabetically unless serde_json's `preserve_order` feature is enabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<String>)` - The keys of the object.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn keys(&self) -> Result<Vec<String>, NanoDBError> {
+        match &self.inner {
+            serde_json::Value::Object(map) => Ok(map.keys().cloned().collect()),
+            _ => Err(NanoDBError::NotAnObject(self.path_string())),
+        }
+    }
+
+    /// Checks if the inner JSON object of the tree contains the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bool)` - Whether the object contains `key`.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn contains_key(&self, key: &str) -> Result<bool, NanoDBError> {
+        match &self.inner {
+            serde_json::Value::Object(map) => Ok(map.contains_key(key)),
+            _ => Err(NanoDBError::NotAnObject(key.to_string())),
+        }
+    }
+
+    /// Returns the values of the inner JSON object of the tree as subtrees.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Tree>)` - One Tree per member of the object, each with its path extended by the member's key.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn values(&self) -> Result<Vec<Tree>, NanoDBError> {
+        Ok(self.entries()?.into_iter().map(|(_, tree)| tree).collect())
+    }
+
+    /// Returns the members of the inner JSON object of the tree as key/subtree pairs.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<(String, Tree)>)` - One pair per member of the object. Each Tree's path is extended by the member's key.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn entries(&self) -> Result<Vec<(String, Tree)>, NanoDBError> {
+        match &self.inner {
+            serde_json::Value::Object(map) => Ok(map
+                .iter()
+                .map(|(key, value)| {
+                    let mut new_path: Vec<PathStep> = self.path.clone();
+                    new_path.push(PathStep::Key(key.clone()));
+                    (key.clone(), Tree::new(value.clone(), new_path))
+                })
+                .collect()),
+            _ => Err(NanoDBError::NotAnObject(self.path_string())),
+        }
+    }
+
+    /// Checks if the inner array of the tree contains the given value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to look for. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bool)` - Whether the array contains an element equal to `value`.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn contains<T: Serialize>(&self, value: T) -> Result<bool, NanoDBError> {
+        let value = serde_json::to_value(value)?;
+        match &self.inner {
+            serde_json::Value::Array(arr) => Ok(arr.contains(&value)),
+            _ => Err(NanoDBError::NotAnArray(self.path_string())),
+        }
+    }
+
+    /// Gets the entry for a key of the inner JSON object of the tree for in-place insertion or modification,
+    /// similar to `HashMap::entry`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the entry.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Entry)` - `Entry::Occupied` if `key` exists, `Entry::Vacant` otherwise.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```text
+    /// tree.entry("counter")?
+    ///     .and_modify(|v| *v = (v.as_i64().unwrap() + 1).into())
+    ///     .or_insert(1)?;
+    /// ```
+    pub fn entry(&mut self, key: &str) -> Result<Entry<'_>, NanoDBError> {
+        let map = self
+            .inner
+            .as_object()
+            .ok_or_else(|| NanoDBError::NotAnObject(key.to_string()))?;
+        let key = key.to_string();
+        if map.contains_key(&key) {
+            Ok(Entry::Occupied(OccupiedEntry { tree: self, key }))
+        } else {
+            Ok(Entry::Vacant(VacantEntry { tree: self, key }))
+        }
+    }
+
+    /// Checks if the inner JSON array of the Tree instance is empty.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - If the inner JSON value empty.
+    /// * `false` - If the inner JSON value is not empty.
+    pub fn is_empty(&self) -> bool {
+        match &self.inner {
+            serde_json::Value::Array(arr) => arr.is_empty(),
+            serde_json::Value::Object(obj) => obj.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// A view into a single key of the inner JSON object of a tree, see `Tree::entry`.
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// The key exists.
+    Occupied(OccupiedEntry<'a>),
+    /// The key does not exist.
+    Vacant(VacantEntry<'a>),
+}
+
+/// An entry for a key that exists, see `Entry`.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    tree: &'a mut Tree,
+    key: String,
+}
+
+/// An entry for a key that does not exist, see `Entry`.
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    tree: &'a mut Tree,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => &entry.key,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant and returns the value of the entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - The value to insert. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the value of the entry.
+    /// * `Err(serde_json::Error)` - If there was an error serializing `default`.
+    pub fn or_insert<T: Serialize>(self, default: T) -> Result<Tree, NanoDBError> {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant and returns the value of the entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - A function producing the value to insert. It is only called if the entry is vacant.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the value of the entry.
+    /// * `Err(serde_json::Error)` - If there was an error serializing the produced value.
+    pub fn or_insert_with<T, F>(self, default: F) -> Result<Tree, NanoDBError>
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.tree.get(&entry.key),
+            Entry::Vacant(entry) => {
+                entry.tree.insert(&entry.key, default())?;
+                entry.tree.get(&entry.key)
+            }
+        }
+    }
+
+    /// Modifies the value in place if the entry is occupied.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function that takes a mutable reference to the `serde_json::Value` of the entry.
+    ///
+    /// # Returns
+    ///
+    /// * `Entry` - The entry itself. This allows for method chaining, e.g. with `or_insert`.
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut serde_json::Value),
+    {
+        match self {
+            Entry::Occupied(entry) => {
+                if let Some(value) = entry
+                    .tree
+                    .inner
+                    .as_object_mut()
+                    .and_then(|map| map.get_mut(&entry.key))
+                {
+                    f(value);
+                }
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        error::NanoDBError,
+        trees::{
+            tree::{ArrayMerge, DiffKind, Entry, PathStep, Tree},
+            tree_helper,
+        },
+    };
+    use serde_json::{json, Value};
+
+    fn value() -> Value {
+        serde_json::from_str(
+            r#"{
+			"key1": "value1",
+			"key2": {
+				"inner_key1": "inner_value1",
+				"inner_key2": "inner_value2"
+			},
+			"key3": [1, 2, 3]
+		}"#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tree_get() {
+        let tree1 = Tree::new(value(), vec![]).get("key1").unwrap();
+        assert_eq!(tree1.inner(), json!("value1"));
+
+        let tree2 = Tree::new(value(), vec![]).get("key2").unwrap();
+        assert_eq!(
+            tree2.inner(),
+            json!({
+                "inner_key1": "inner_value1",
+                "inner_key2": "inner_value2"
+            })
+        );
+
+        // This one should return an error
         let tree3 = Tree::new(value(), vec![]).get("this-key-does-not-exist");
         assert!(tree3.is_err());
         assert!(matches!(tree3.unwrap_err(), NanoDBError::KeyNotFound(_)));
     }
 
     #[tokio::test]
-    async fn test_tree_at() {
-        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
-        let tree = tree.at(1).unwrap();
-        assert_eq!(tree.inner(), serde_json::json!(2));
+    async fn test_tree_at() {
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        let tree = tree.at(1).unwrap();
+        assert_eq!(tree.inner(), serde_json::json!(2));
+    }
+
+    #[tokio::test]
+    async fn test_tree_get_path() {
+        let data = json!({"a": {"b": [{"c": 1}, {"c": 2}]}, "d": [[1, 2], [3, 4]]});
+        let tree = Tree::new(data, vec![]);
+
+        let x = tree.get_path("a.b[1].c").unwrap();
+        assert_eq!(x.inner(), json!(2));
+        assert_eq!(x.path_string(), "a.b.[1].c");
+        assert_eq!(tree.get_path("d[1][0]").unwrap().inner(), json!(3));
+        assert_eq!(tree.get_path("").unwrap().inner(), tree.inner());
+
+        // errors
+        assert!(matches!(
+            tree.get_path("a.x").unwrap_err(),
+            NanoDBError::PathNotFound { path, missing } if path == "a" && missing == "x"
+        ));
+        assert!(matches!(
+            tree.get_path("a.b[1].x").unwrap_err(),
+            NanoDBError::PathNotFound { path, missing } if path == "a.b.[1]" && missing == "x"
+        ));
+        assert!(matches!(
+            tree.get("a").unwrap().get_path("b[0].x").unwrap_err(),
+            NanoDBError::PathNotFound { path, missing } if path == "a.b.[0]" && missing == "x"
+        ));
+        assert!(matches!(
+            tree.get_path("a.b[5]").unwrap_err(),
+            NanoDBError::IndexOutOfBounds(5)
+        ));
+        assert!(matches!(
+            tree.get_path("d.x").unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+        assert!(matches!(
+            tree.get_path("a[0]").unwrap_err(),
+            NanoDBError::NotAnArray(_)
+        ));
+        assert!(matches!(
+            tree.get_path("a..b").unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+        assert!(matches!(
+            tree.get_path("d[x]").unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_pointer() {
+        let data = json!({"key3": {"versions": [1.0, 2.0]}, "a/b": {"m~n": 42}});
+        let tree = Tree::new(data, vec![]);
+
+        let x = tree.pointer("/key3/versions/1").unwrap();
+        assert_eq!(x.inner(), json!(2.0));
+        assert_eq!(x.path_string(), "key3.versions.[1]");
+        assert_eq!(tree.pointer("/a~1b/m~0n").unwrap().inner(), json!(42));
+        assert_eq!(tree.pointer("").unwrap().inner(), tree.inner());
+
+        // tokens that don't resolve
+        for pointer in [
+            "/key3/versions/2",
+            "/key3/versions/01",
+            "/key3/versions/-",
+            "/key4",
+            "key3",
+        ] {
+            assert!(matches!(
+                tree.pointer(pointer).unwrap_err(),
+                NanoDBError::InvalidJSONPath
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tree_insert() {
+        let mut tree = Tree::new(value(), vec![]);
+        tree.insert("new_key", "new_value").unwrap();
+        let tree = tree.get("new_key").unwrap();
+        assert_eq!(tree.inner(), serde_json::json!("new_value"));
+
+        // inserting into an array should fail
+        let tree = Tree::new(value(), vec![]);
+        let x = tree.get("key3").unwrap().insert("new_key", "new_value");
+        assert!(x.is_err());
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnObject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_get_or_insert_with() {
+        let mut tree = Tree::new(value(), vec![]);
+
+        // existing key: the default is not used
+        let x = tree.get_or_insert_with("key1", || "default").unwrap();
+        assert_eq!(x.inner(), json!("value1"));
+
+        // missing key: the default is inserted
+        let x = tree.get_or_insert_with("counter", || 0).unwrap();
+        assert_eq!(x.inner(), json!(0));
+        assert_eq!(x.path_string(), "counter");
+        assert_eq!(tree.get("counter").unwrap().inner(), json!(0));
+
+        // not an object
+        let mut tree = tree.get("key3").unwrap();
+        let x = tree.get_or_insert_with("counter", || 0);
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnObject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_remove() {
+        let mut tree = Tree::new(value(), vec![]);
+        tree.remove("key1").unwrap();
+        let x = tree.get("key1");
+        assert!(x.is_err());
+        assert!(matches!(x.unwrap_err(), NanoDBError::KeyNotFound(_)));
+
+        // This one should return a KeyNotFound error
+        let tree3 = Tree::new(value(), vec![]).remove("this-key-does-not-exist");
+        assert!(tree3.is_err());
+        assert!(matches!(tree3.unwrap_err(), NanoDBError::KeyNotFound(_)));
+
+        // This one should return a NotAnObject error
+        let tree4 = Tree::new(value(), vec![])
+            .get("key3")
+            .unwrap()
+            .remove("key1");
+        assert!(tree4.is_err());
+        assert!(matches!(tree4.unwrap_err(), NanoDBError::NotAnObject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_rename_key() {
+        let mut tree = Tree::new(value(), vec![]);
+        tree.rename_key("key1", "renamed").unwrap();
+        assert!(!tree.contains_key("key1").unwrap());
+        assert_eq!(tree.get("renamed").unwrap().inner(), json!("value1"));
+
+        // missing source key
+        let x = tree.rename_key("key1", "other");
+        assert!(matches!(x.unwrap_err(), NanoDBError::KeyNotFound(_)));
+
+        // overwrite an existing key
+        tree.rename_key("renamed", "key3").unwrap();
+        assert_eq!(tree.get("key3").unwrap().inner(), json!("value1"));
+        assert_eq!(tree.len().unwrap(), 2);
+
+        // not an object
+        let mut tree = tree.get("key2").unwrap().get("inner_key1").unwrap();
+        let x = tree.rename_key("a", "b");
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnObject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_remove_at() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+
+        // assert index out of bounds error
+        let out_of_bounds = tree.remove_at(4);
+        assert!(out_of_bounds.is_err());
+        assert!(matches!(
+            out_of_bounds.unwrap_err(),
+            NanoDBError::IndexOutOfBounds(_)
+        ));
+
+        // remove the first element
+        tree.remove_at(0).unwrap();
+        assert_eq!(tree.inner(), json!([2, 3]));
+
+        // This one must be out of bounds now
+        let x = tree.at(3);
+        assert!(x.is_err());
+        assert!(matches!(x.unwrap_err(), NanoDBError::IndexOutOfBounds(_)));
+
+        // test not an array error
+        let mut tree = Tree::new(value(), vec![]).get("key1").unwrap();
+        let tree2 = tree.remove_at(0);
+        assert!(tree2.is_err());
+        assert!(matches!(tree2.unwrap_err(), NanoDBError::NotAnArray(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_remove_elem() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        tree.remove_elem(2).unwrap();
+        assert_eq!(tree.inner(), json!([1, 3]));
+
+        // remove a string
+        let mut tree = Tree::new(json!(["a", "b", "a"]), vec![]);
+        tree.remove_elem("a").unwrap();
+        assert_eq!(tree.inner(), json!(["b", "a"]));
+
+        // element not found
+        let x = tree.remove_elem("c");
+        assert!(matches!(x.unwrap_err(), NanoDBError::ElementNotFound(_)));
+
+        // not an array
+        let mut tree = Tree::new(value(), vec![]).get("key1").unwrap();
+        let x = tree.remove_elem(1);
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnArray(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_merge_deep_from() {
+        let mut tree = Tree::new(json!({"a": 1, "b": {"c": 1, "d": [1]}}), vec![]);
+        let other = Tree::new(
+            json!({"x": 2, "d": [2], "e": {"f": true}}),
+            vec![PathStep::Key("b".to_string())],
+        );
+        tree.merge_deep_from(other.clone()).unwrap();
+        assert_eq!(
+            tree.inner(),
+            json!({"a": 1, "b": {"c": 1, "d": [1, 2], "e": {"f": true}, "x": 2}})
+        );
+
+        // replace arrays instead of concatenating them
+        let mut tree = Tree::new(json!({"a": 1, "b": {"c": 1, "d": [1]}}), vec![]);
+        tree.merge_deep_from_with(other, ArrayMerge::Replace)
+            .unwrap();
+        assert_eq!(tree.get_path("b.d").unwrap().inner(), json!([2]));
+
+        // mixed types: the other tree wins
+        let mut tree = Tree::new(json!({"a": {"b": 1}, "c": [1], "d": "x"}), vec![]);
+        let other = Tree::new(json!({"a": 5, "c": {"k": 1}, "d": [1]}), vec![]);
+        tree.merge_deep_from(other).unwrap();
+        assert_eq!(tree.inner(), json!({"a": 5, "c": {"k": 1}, "d": [1]}));
+
+        // invalid path
+        let other = Tree::new(json!({}), vec![PathStep::Key("missing".to_string())]);
+        assert!(matches!(
+            tree.merge_deep_from(other).unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_push() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        tree.push(10).unwrap();
+        assert!(tree
+            .inner()
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!(10)));
+
+        // push into object -> must fail
+        let mut tree = Tree::new(value(), vec![]).get("key1").unwrap();
+        let x = tree.push(42);
+        assert!(x.is_err());
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnArray(_)));
+    }
+    #[tokio::test]
+    async fn test_tree_extend() {
+        let mut tree = Tree::new(json!([1]), vec![]);
+        tree.extend(vec![2, 3]).unwrap();
+        assert_eq!(tree.inner(), json!([1, 2, 3]));
+
+        let mut tree = Tree::new(value(), vec![]);
+        assert!(matches!(
+            tree.extend(vec![1]).unwrap_err(),
+            NanoDBError::NotAnArray(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_insert_at() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        tree.insert_at(0, 0).unwrap();
+        assert_eq!(tree.inner(), json!([0, 1, 2, 3]));
+        tree.insert_at(2, 42).unwrap();
+        assert_eq!(tree.inner(), json!([0, 1, 42, 2, 3]));
+        tree.insert_at(5, 4).unwrap();
+        assert_eq!(tree.inner(), json!([0, 1, 42, 2, 3, 4]));
+
+        // index past the end must fail
+        let x = tree.insert_at(7, 5);
+        assert!(matches!(x.unwrap_err(), NanoDBError::IndexOutOfBounds(7)));
+
+        // insert into object -> must fail
+        let mut tree = Tree::new(value(), vec![]);
+        let x = tree.insert_at(0, 1);
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnArray(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_sort() {
+        let mut tree = Tree::new(json!([3, 1.5, 2]), vec![]);
+        tree.sort().unwrap();
+        assert_eq!(tree.inner(), json!([1.5, 2, 3]));
+
+        // integers beyond 2^53 are compared exactly
+        let mut tree = Tree::new(json!([9007199254740993u64, 9007199254740992u64]), vec![]);
+        tree.sort().unwrap();
+        assert_eq!(
+            tree.inner(),
+            json!([9007199254740992u64, 9007199254740993u64])
+        );
+        let mut tree = Tree::new(json!([u64::MAX, -1, 0.5, i64::MIN]), vec![]);
+        tree.sort().unwrap();
+        assert_eq!(tree.inner(), json!([i64::MIN, -1, 0.5, u64::MAX]));
+
+        let mut tree = Tree::new(json!(["b", "c", "a"]), vec![]);
+        tree.sort().unwrap();
+        assert_eq!(tree.inner(), json!(["a", "b", "c"]));
+
+        // mixed types
+        let mut tree = Tree::new(json!([1, "a"]), vec![]);
+        assert!(matches!(
+            tree.sort().unwrap_err(),
+            NanoDBError::Unsortable(_)
+        ));
+        let mut tree = Tree::new(json!([[1], [0]]), vec![]);
+        assert!(matches!(
+            tree.sort().unwrap_err(),
+            NanoDBError::Unsortable(_)
+        ));
+
+        // custom comparator
+        let mut tree = Tree::new(json!([{"id": 2}, {"id": 1}]), vec![]);
+        tree.sort_by(|a, b| a["id"].as_i64().cmp(&b["id"].as_i64()))
+            .unwrap();
+        assert_eq!(tree.inner(), json!([{"id": 1}, {"id": 2}]));
+    }
+
+    #[tokio::test]
+    async fn test_tree_reverse() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        tree.reverse().unwrap();
+        assert_eq!(tree.inner(), json!([3, 2, 1]));
+
+        let mut tree = Tree::new(value(), vec![]);
+        assert!(matches!(
+            tree.reverse().unwrap_err(),
+            NanoDBError::NotAnArray(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_for_each() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        tree.for_each(|v| {
+            *v = Value::from(v.as_i64().unwrap() + 2i64);
+        })
+        .unwrap();
+        assert_eq!(tree.inner(), json!([3, 4, 5]));
+    }
+
+    #[tokio::test]
+    async fn test_tree_retain() {
+        let mut tree = Tree::new(json!([1, 2, 3, 4]), vec![]);
+        tree.retain(|v| v.as_i64().unwrap() % 2 == 0).unwrap();
+        assert_eq!(tree.inner(), json!([2, 4]));
+
+        let mut tree = Tree::new(value(), vec![]);
+        assert!(matches!(
+            tree.retain(|_| true).unwrap_err(),
+            NanoDBError::NotAnArray(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_find() {
+        let data = json!({"users": [{"id": 1, "name": "Ada"}, {"id": 7, "name": "Linus"}]});
+        let tree = Tree::new(data, vec![]).get("users").unwrap();
+
+        let user = tree.find(|v| v["id"] == json!(7)).unwrap().unwrap();
+        assert_eq!(user.get("name").unwrap().inner(), json!("Linus"));
+        assert_eq!(user.path_string(), "users.[1]");
+        assert!(tree.find(|v| v["id"] == json!(8)).unwrap().is_none());
+
+        let tree = Tree::new(value(), vec![]);
+        assert!(matches!(
+            tree.find(|_| true).unwrap_err(),
+            NanoDBError::NotAnArray(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_position() {
+        let data = json!([{"id": 3}, {"id": 7}, {"id": 7}]);
+        let tree = Tree::new(data, vec![]);
+        assert_eq!(tree.position(|v| v["id"] == json!(7)).unwrap(), Some(1));
+        assert_eq!(tree.position(|v| v["id"] == json!(8)).unwrap(), None);
+
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        assert_eq!(tree.position(|v| *v == json!(3)).unwrap(), Some(2));
+
+        let tree = Tree::new(value(), vec![]);
+        assert!(matches!(
+            tree.position(|_| true).unwrap_err(),
+            NanoDBError::NotAnArray(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_clear() {
+        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        tree.clear().unwrap();
+        assert_eq!(tree.inner(), json!([]));
+
+        let mut tree = Tree::new(value(), vec![]).get("key2").unwrap();
+        tree.clear().unwrap();
+        assert_eq!(tree.inner(), json!({}));
+
+        let mut tree = Tree::new(value(), vec![]).get("key1").unwrap();
+        assert!(matches!(
+            tree.clear().unwrap_err(),
+            NanoDBError::LenNotDefined(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_len() {
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        assert_eq!(tree.len().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_tree_keys() {
+        let tree = Tree::new(value(), vec![]).get("key2").unwrap();
+        assert_eq!(tree.keys().unwrap(), vec!["inner_key1", "inner_key2"]);
+
+        // keys of an array -> must fail
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        let x = tree.keys();
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnObject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_contains_key() {
+        let tree = Tree::new(value(), vec![]);
+        assert!(tree.contains_key("key1").unwrap());
+        assert!(!tree.contains_key("this-key-does-not-exist").unwrap());
+
+        // contains_key on an array -> must fail
+        let tree = tree.get("key3").unwrap();
+        let x = tree.contains_key("key1");
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnObject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_values_and_entries() {
+        let tree = Tree::new(value(), vec![]).get("key2").unwrap();
+
+        let values = tree.values().unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].inner(), json!("inner_value1"));
+        assert_eq!(values[0].path_string(), "key2.inner_key1");
+
+        let entries = tree.entries().unwrap();
+        assert_eq!(entries[1].0, "inner_key2");
+        assert_eq!(entries[1].1.inner(), json!("inner_value2"));
+        assert_eq!(entries[1].1.path_string(), "key2.inner_key2");
+
+        // values of an array -> must fail
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        assert!(matches!(
+            tree.values().unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+        assert!(matches!(
+            tree.entries().unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_contains() {
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        assert!(tree.contains(2).unwrap());
+        assert!(!tree.contains(42).unwrap());
+
+        // contains on an object -> must fail
+        let tree = Tree::new(value(), vec![]).get("key2").unwrap();
+        let x = tree.contains("inner_value1");
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnArray(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tree_into() {
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        let arr: Vec<i64> = tree.into().unwrap();
+        assert_eq!(arr, vec![1, 2, 3]);
+
+        // the following one must fail (type mismatch)
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        let x: Result<Vec<String>, NanoDBError> = tree.into();
+        assert!(x.is_err());
+        assert!(matches!(x.unwrap_err(), NanoDBError::TypeMismatch { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_tree_scalar_accessors() {
+        let tree = Tree::new(json!({"s": "text", "i": -3, "f": 1.5, "b": true}), vec![]);
+        assert_eq!(tree.get("s").unwrap().as_str(), Some("text"));
+        assert_eq!(tree.get("i").unwrap().as_i64(), Some(-3));
+        assert_eq!(tree.get("i").unwrap().as_f64(), Some(-3.0));
+        assert_eq!(tree.get("f").unwrap().as_f64(), Some(1.5));
+        assert_eq!(tree.get("b").unwrap().as_bool(), Some(true));
+
+        assert_eq!(tree.get("i").unwrap().as_str(), None);
+        assert_eq!(tree.get("f").unwrap().as_i64(), None);
+        assert_eq!(tree.get("s").unwrap().as_f64(), None);
+        assert_eq!(tree.get("s").unwrap().as_bool(), None);
+        assert_eq!(tree.as_str(), None);
+    }
+
+    #[tokio::test]
+    async fn test_tree_index() {
+        let tree = Tree::new(value(), vec![]);
+        assert_eq!(tree["key1"], json!("value1"));
+        assert_eq!(tree["key2"]["inner_key2"], json!("inner_value2"));
+        assert_eq!(tree["key3"][1], json!(2));
+        assert_eq!(tree.get("key3").unwrap()[2], json!(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "key 'missing' not found")]
+    fn test_tree_index_missing_key_panics() {
+        let tree = Tree::new(value(), vec![]);
+        let _ = &tree["missing"];
+    }
+
+    #[test]
+    fn test_tree_index_chained_miss_is_null() {
+        let tree = Tree::new(value(), vec![]);
+        // only the first step panics, deeper steps follow serde_json::Value's rules
+        assert_eq!(tree["key2"]["missing"], serde_json::Value::Null);
+        assert_eq!(tree["key3"][5], serde_json::Value::Null);
+        assert!(tree.get_path("key2.missing").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 not found")]
+    fn test_tree_index_out_of_bounds_panics() {
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+        let _ = &tree[3];
+    }
+
+    #[tokio::test]
+    async fn test_tree_into_iter() {
+        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
+
+        let elems: Vec<Tree> = (&tree).into_iter().collect();
+        assert_eq!(elems.len(), 3);
+        assert_eq!(elems[1].inner(), json!(2));
+        assert_eq!(elems[1].path_string(), "key3.[1]");
+
+        let mut values = vec![];
+        for elem in tree {
+            values.push(elem.inner());
+        }
+        assert_eq!(values, vec![json!(1), json!(2), json!(3)]);
+
+        let object = Tree::new(value(), vec![]);
+        assert_eq!(object.into_iter().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tree_serde_round_trip() {
+        let tree = Tree::new(value(), vec![]).get("key2").unwrap();
+        let serialized = serde_json::to_string(&tree).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"inner_key1":"inner_value1","inner_key2":"inner_value2"}"#
+        );
+
+        let deserialized: Tree = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.inner(), tree.inner());
+        assert_eq!(deserialized.path_string(), "");
+
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Wrapper {
+            name: String,
+            data: Tree,
+        }
+        let wrapper: Wrapper =
+            serde_json::from_value(json!({"name": "w", "data": [1, 2]})).unwrap();
+        assert_eq!(wrapper.data.inner(), json!([1, 2]));
+        assert_eq!(
+            serde_json::to_value(&wrapper).unwrap(),
+            json!({"name": "w", "data": [1, 2]})
+        );
+
+        let tree = Tree::from_value(json!([1, 2, 3]));
+        assert_eq!(tree.inner(), json!([1, 2, 3]));
+        assert_eq!(tree.path_string(), "");
+    }
+
+    #[tokio::test]
+    async fn test_tree_partial_eq() {
+        let tree = Tree::new(value(), vec![]);
+        let key3 = tree.get("key3").unwrap();
+        assert_eq!(key3, Tree::new(json!([1, 2, 3]), vec![]));
+        assert_eq!(key3, json!([1, 2, 3]));
+        assert_ne!(key3, json!([1, 2]));
+        assert_ne!(key3, tree);
+    }
+
+    #[tokio::test]
+    async fn test_tree_from_value() {
+        let mut tree: Tree = json!({"a": 1}).into();
+        assert_eq!(tree.path_string(), "");
+        let tree = tree.insert("b", [1, 2]).unwrap();
+        assert_eq!(tree, json!({"a": 1, "b": [1, 2]}));
+    }
+
+    #[tokio::test]
+    async fn test_tree_get_optional() {
+        let tree = Tree::new(value(), vec![]);
+        let key1 = tree.get_optional("key1").unwrap().unwrap();
+        assert_eq!(key1, json!("value1"));
+        assert_eq!(key1.path_string(), "key1");
+        assert!(tree.get_optional("missing").unwrap().is_none());
+        assert!(matches!(
+            tree.get("key3").unwrap().get_optional("key1").unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_merge_into() {
+        let mut parent = Tree::new(value(), vec![]);
+
+        let mut key2 = parent.get("key2").unwrap();
+        key2.insert("inner_key3", "inner_value3").unwrap();
+        key2.merge_into(&mut parent).unwrap();
+        assert_eq!(
+            parent.get("key2").unwrap(),
+            json!({
+                "inner_key1": "inner_value1",
+                "inner_key2": "inner_value2",
+                "inner_key3": "inner_value3"
+            })
+        );
+
+        let elem = Tree::new(
+            json!(20),
+            vec![PathStep::Key("key3".into()), PathStep::Index(1)],
+        );
+        elem.merge_into(&mut parent).unwrap();
+        assert_eq!(parent.get("key3").unwrap(), json!([1, 20, 3]));
+
+        // errors
+        let missing = Tree::new(json!(1), vec![PathStep::Key("missing".into())]);
+        assert!(matches!(
+            missing.merge_into(&mut parent).unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+        let out_of_bounds = Tree::new(
+            json!(1),
+            vec![PathStep::Key("key3".into()), PathStep::Index(9)],
+        );
+        assert!(matches!(
+            out_of_bounds.merge_into(&mut parent).unwrap_err(),
+            NanoDBError::IndexOutOfBounds(9)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_is_root() {
+        let tree = Tree::new(value(), vec![]);
+        assert!(tree.is_root());
+        assert!(!tree.get("key3").unwrap().is_root());
+        assert!(!tree.get_path("key3[0]").unwrap().is_root());
+        assert!(Tree::from_value(json!(1)).is_root());
     }
 
     #[tokio::test]
-    async fn test_tree_insert() {
-        let mut tree = Tree::new(value(), vec![]);
-        tree.insert("new_key", "new_value").unwrap();
-        let tree = tree.get("new_key").unwrap();
-        assert_eq!(tree.inner(), serde_json::json!("new_value"));
+    async fn test_tree_depth_and_count_nodes() {
+        let flat = Tree::from_value(json!({"a": 1, "b": "two", "c": null}));
+        assert_eq!(flat.depth(), 1);
+        assert_eq!(flat.count_nodes(), 4);
 
-        // inserting into an array should fail
         let tree = Tree::new(value(), vec![]);
-        let x = tree.get("key3").unwrap().insert("new_key", "new_value");
-        assert!(x.is_err());
-        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnObject(_)));
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.count_nodes(), 9);
+
+        let nested = Tree::from_value(json!({"a": [{"b": [1, [2]]}, {}], "c": []}));
+        assert_eq!(nested.depth(), 5);
+        assert_eq!(nested.count_nodes(), 9);
+
+        assert_eq!(Tree::from_value(json!(1)).depth(), 0);
+        assert_eq!(Tree::from_value(json!(1)).count_nodes(), 1);
+        assert_eq!(Tree::from_value(json!([])).depth(), 1);
     }
 
     #[tokio::test]
-    async fn test_tree_remove() {
+    async fn test_tree_walk() {
+        let tree = Tree::new(value(), vec![]).get("key2").unwrap();
+        let mut paths = vec![];
+        tree.walk(|path, _| paths.push(tree_helper::path_to_string(path)));
+        assert_eq!(paths, vec!["", "inner_key1", "inner_key2"]);
+
+        let tree = Tree::from_value(json!({"a": [1, {"b": true}], "c": null}));
+        let mut visited = vec![];
+        tree.walk(|path, value| visited.push((tree_helper::path_to_string(path), value.clone())));
+        assert_eq!(
+            visited,
+            vec![
+                ("".to_string(), tree.inner()),
+                ("a".to_string(), json!([1, {"b": true}])),
+                ("a.[0]".to_string(), json!(1)),
+                ("a.[1]".to_string(), json!({"b": true})),
+                ("a.[1].b".to_string(), json!(true)),
+                ("c".to_string(), json!(null)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tree_walk_mut() {
         let mut tree = Tree::new(value(), vec![]);
-        tree.remove("key1").unwrap();
-        let x = tree.get("key1");
-        assert!(x.is_err());
-        assert!(matches!(x.unwrap_err(), NanoDBError::KeyNotFound(_)));
+        tree.walk_mut(|path, value| {
+            if let Some(n) = value.as_i64() {
+                *value = json!(n * 10);
+            }
+            if path.len() == 1 && value.is_string() {
+                *value = json!([value.clone()]);
+            }
+        });
+        assert_eq!(tree.get("key3").unwrap(), json!([10, 20, 30]));
+        assert_eq!(tree.get("key1").unwrap(), json!(["value1"]));
+    }
 
-        // This one should return a KeyNotFound error
-        let tree3 = Tree::new(value(), vec![]).remove("this-key-does-not-exist");
-        assert!(tree3.is_err());
-        assert!(matches!(tree3.unwrap_err(), NanoDBError::KeyNotFound(_)));
+    #[tokio::test]
+    async fn test_tree_flatten() {
+        let tree = Tree::from_value(json!({
+            "key1": "Welcome!",
+            "key2": 42,
+            "key3": {
+                "name": "NanoDB",
+                "versions": [1.0, 2.0, 3.0]
+            },
+            "key4": [1, 2, 3],
+            "key5": ["Welcome", "to", "NanoDB"],
+            "key6": {}
+        }));
+        let flat = tree.flatten();
+        assert_eq!(flat.len(), 13);
+        assert!(flat.contains(&("key1".to_string(), json!("Welcome!"))));
+        assert!(flat.contains(&("key3.name".to_string(), json!("NanoDB"))));
+        assert!(flat.contains(&("key3.versions[2]".to_string(), json!(3.0))));
+        assert!(flat.contains(&("key5[0]".to_string(), json!("Welcome"))));
+        assert!(flat.contains(&("key6".to_string(), json!({}))));
+        for (path, value) in &flat {
+            assert_eq!(&tree.get_path(path).unwrap().inner(), value);
+        }
 
-        // This one should return a NotAnObject error
-        let tree4 = Tree::new(value(), vec![])
-            .get("key3")
-            .unwrap()
-            .remove("key1");
-        assert!(tree4.is_err());
-        assert!(matches!(tree4.unwrap_err(), NanoDBError::NotAnObject(_)));
+        assert_eq!(
+            Tree::from_value(json!("scalar")).flatten(),
+            vec![("".to_string(), json!("scalar"))]
+        );
     }
 
     #[tokio::test]
-    async fn test_tree_remove_at() {
-        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
+    async fn test_tree_unflatten() {
+        let tree = Tree::from_value(json!({
+            "key1": "Welcome!",
+            "key3": {"name": "NanoDB", "versions": [1.0, 2.0, {"x": []}]},
+            "key4": [[1, 2], [3]],
+            "key6": {}
+        }));
+        let rebuilt = Tree::unflatten(tree.flatten()).unwrap();
+        assert_eq!(rebuilt, tree);
+        assert!(rebuilt.is_root());
 
-        // assert index out of bounds error
-        let out_of_bounds = tree.remove_at(4);
-        assert!(out_of_bounds.is_err());
+        let sparse = Tree::unflatten(vec![("a[2]".to_string(), json!(1))]).unwrap();
+        assert_eq!(sparse, json!({"a": [null, null, 1]}));
+        assert_eq!(Tree::unflatten(vec![]).unwrap(), json!({}));
+
+        // conflicting paths
+        let conflicts = vec![
+            vec![
+                ("a.b".to_string(), json!(1)),
+                ("a[0]".to_string(), json!(2)),
+            ],
+            vec![("a".to_string(), json!(1)), ("a.b".to_string(), json!(2))],
+            vec![("a.b".to_string(), json!(1)), ("a.b".to_string(), json!(2))],
+            vec![("a..b".to_string(), json!(1))],
+        ];
+        for pairs in conflicts {
+            assert!(matches!(
+                Tree::unflatten(pairs).unwrap_err(),
+                NanoDBError::InvalidJSONPath
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tree_set() {
+        let mut key1 = Tree::new(value(), vec![]).get("key1").unwrap();
+        let key1 = key1.set(42).unwrap();
+        assert_eq!(key1, json!(42));
+        assert_eq!(key1.path_string(), "key1");
+
+        let mut key2 = Tree::new(value(), vec![]).get("key2").unwrap();
+        let key2 = key2.set(json!({"replaced": true})).unwrap();
+        assert_eq!(key2, json!({"replaced": true}));
+
+        let mut tree = Tree::new(value(), vec![]);
+        tree.merge_from(key2).unwrap();
+        assert_eq!(tree.get("key2").unwrap(), json!({"replaced": true}));
+        assert_eq!(tree.get("key1").unwrap(), json!("value1"));
+    }
+
+    #[tokio::test]
+    async fn test_tree_modify() {
+        let mut tree = Tree::from_value(json!({"a": {"b": [{"count": 1}, {"count": 5}]}}));
+        tree.modify("a.b[1].count", |v| *v = json!(v.as_i64().unwrap() + 1))
+            .unwrap();
+        assert_eq!(tree.get_path("a.b[1].count").unwrap(), json!(6));
+        assert_eq!(tree.get_path("a.b[0].count").unwrap(), json!(1));
+
+        for path in ["a.x", "a.b[2].count", "a.b.count", "a..b"] {
+            assert!(matches!(
+                tree.modify(path, |v| *v = json!(null)).unwrap_err(),
+                NanoDBError::InvalidJSONPath
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_null() {
+        let tree = Tree::from_value(json!({"key": "value"}))
+            .insert("nothing", Tree::null())
+            .unwrap();
+
+        let nothing = tree.get("nothing").unwrap();
+        assert!(nothing.is_null());
+        assert_eq!(nothing.inner(), Value::Null);
+        assert!(!tree.get("key").unwrap().is_null());
         assert!(matches!(
-            out_of_bounds.unwrap_err(),
-            NanoDBError::IndexOutOfBounds(_)
+            tree.get("missing"),
+            Err(NanoDBError::KeyNotFound(_))
         ));
+        assert!(Tree::null().is_root());
+    }
 
-        // remove the first element
-        tree.remove_at(0).unwrap();
-        assert_eq!(tree.inner(), json!([2, 3]));
+    #[tokio::test]
+    async fn test_take() {
+        let mut tree = Tree::from_value(json!({"key1": "value1", "key2": {"inner": [1, 2]}}));
 
-        // This one must be out of bounds now
-        let x = tree.at(3);
-        assert!(x.is_err());
-        assert!(matches!(x.unwrap_err(), NanoDBError::IndexOutOfBounds(_)));
+        let taken = tree.take("key2").unwrap();
+        assert_eq!(taken.inner(), json!({"inner": [1, 2]}));
+        assert_eq!(taken.path_string(), "key2");
+        assert_eq!(tree.inner(), json!({"key1": "value1"}));
 
-        // test not an array error
-        let mut tree = Tree::new(value(), vec![]).get("key1").unwrap();
-        let tree2 = tree.remove_at(0);
-        assert!(tree2.is_err());
-        assert!(matches!(tree2.unwrap_err(), NanoDBError::NotAnArray(_)));
+        assert!(matches!(
+            tree.take("key2"),
+            Err(NanoDBError::KeyNotFound(_))
+        ));
+        assert!(matches!(
+            tree.get("key1").unwrap().take("key"),
+            Err(NanoDBError::NotAnObject(_))
+        ));
     }
 
     #[tokio::test]
-    async fn test_push() {
-        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
-        tree.push(10).unwrap();
-        assert!(tree
-            .inner()
-            .as_array()
+    async fn test_first_and_last() {
+        let tree = Tree::from_value(json!({"versions": [1.0, 2.0, 3.0], "empty": []}));
+
+        let versions = tree.get("versions").unwrap();
+        let first = versions.first().unwrap().unwrap();
+        assert_eq!(first.inner(), json!(1.0));
+        assert_eq!(first.path_string(), "versions.[0]");
+        let last = versions.last().unwrap().unwrap();
+        assert_eq!(last.inner(), json!(3.0));
+        assert_eq!(last.path_string(), "versions.[2]");
+
+        let empty = tree.get("empty").unwrap();
+        assert!(empty.first().unwrap().is_none());
+        assert!(empty.last().unwrap().is_none());
+
+        assert!(matches!(tree.first(), Err(NanoDBError::NotAnArray(_))));
+        assert!(matches!(tree.last(), Err(NanoDBError::NotAnArray(_))));
+    }
+
+    #[tokio::test]
+    async fn test_slice() {
+        let tree = Tree::from_value(json!({"numbers": [1, 2, 3, 4, 5]}));
+        let numbers = tree.get("numbers").unwrap();
+
+        let slice = numbers.slice(1, 3).unwrap();
+        assert_eq!(slice.inner(), json!([2, 3]));
+        assert_eq!(slice.path_string(), "numbers");
+        assert_eq!(numbers.slice(3, 99).unwrap().inner(), json!([4, 5]));
+        assert_eq!(numbers.slice(5, 6).unwrap().inner(), json!([]));
+        assert_eq!(numbers.slice(3, 1).unwrap().inner(), json!([]));
+
+        assert!(matches!(
+            numbers.slice(6, 7),
+            Err(NanoDBError::IndexOutOfBounds(6))
+        ));
+        assert!(matches!(tree.slice(0, 1), Err(NanoDBError::NotAnArray(_))));
+    }
+
+    #[tokio::test]
+    async fn test_map() {
+        let tree = Tree::from_value(json!({"numbers": [1, 2, 3]}));
+        let numbers = tree.get("numbers").unwrap();
+
+        let doubled = numbers
+            .map(|v| Value::from(v.as_i64().unwrap() * 2))
+            .unwrap();
+        assert_eq!(doubled.inner(), json!([2, 4, 6]));
+        assert_eq!(doubled.path_string(), "numbers");
+        assert_eq!(numbers.inner(), json!([1, 2, 3]));
+
+        assert!(matches!(
+            tree.map(|v| v.clone()),
+            Err(NanoDBError::NotAnArray(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pluck() {
+        let tree = Tree::from_value(json!({
+            "users": [
+                {"id": 1, "name": "a"},
+                {"id": 2},
+                {"id": 3, "name": "c"},
+                "not an object"
+            ]
+        }));
+        let users = tree.get("users").unwrap();
+
+        assert_eq!(users.pluck("id").unwrap().inner(), json!([1, 2, 3]));
+        assert_eq!(users.pluck("name").unwrap().inner(), json!(["a", "c"]));
+        assert_eq!(users.pluck("age").unwrap().inner(), json!([]));
+        assert!(matches!(tree.pluck("id"), Err(NanoDBError::NotAnArray(_))));
+
+        assert!(matches!(
+            users.pluck_strict("name"),
+            Err(NanoDBError::KeyNotFound(_))
+        ));
+        assert!(matches!(
+            users.pluck_strict("id"),
+            Err(NanoDBError::NotAnObject(_))
+        ));
+        let names = users.slice(0, 1).unwrap().pluck_strict("name").unwrap();
+        assert_eq!(names.inner(), json!(["a"]));
+    }
+
+    #[tokio::test]
+    async fn test_query() {
+        let tree = Tree::from_value(json!({
+            "users": [
+                {"name": "a", "role": "admin"},
+                {"name": "b", "role": "user"},
+                {"name": "c"},
+                {"name": "d", "role": "admin"},
+                "admin"
+            ]
+        }));
+        let users = tree.get("users").unwrap();
+
+        let admins = users.query("role", "admin").unwrap();
+        assert_eq!(
+            admins.inner(),
+            json!([{"name": "a", "role": "admin"}, {"name": "d", "role": "admin"}])
+        );
+        assert_eq!(admins.pluck("name").unwrap().inner(), json!(["a", "d"]));
+        assert_eq!(users.query("role", "guest").unwrap().inner(), json!([]));
+        assert!(matches!(
+            tree.query("role", "admin"),
+            Err(NanoDBError::NotAnArray(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_many() {
+        let tree = Tree::from_value(json!({"key1": "value1", "key2": 2, "key3": [3]}));
+
+        let values = tree.get_many(&["key3", "key1"]).unwrap();
+        assert_eq!(
+            values.iter().map(Tree::inner).collect::<Vec<_>>(),
+            vec![json!([3]), json!("value1")]
+        );
+        assert_eq!(values[0].path_string(), "key3");
+        assert!(matches!(
+            tree.get_many(&["key1", "key0", "key4"]),
+            Err(NanoDBError::KeyNotFound(key)) if key == "key0"
+        ));
+
+        let values = tree.get_many_optional(&["key0", "key2"]).unwrap();
+        assert!(values[0].is_none());
+        assert_eq!(values[1].as_ref().unwrap().inner(), json!(2));
+
+        let array = tree.get("key3").unwrap();
+        assert!(matches!(
+            array.get_many(&["key"]),
+            Err(NanoDBError::NotAnObject(_))
+        ));
+        assert!(matches!(
+            array.get_many_optional(&["key"]),
+            Err(NanoDBError::NotAnObject(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_at_path() {
+        let mut tree = Tree::from_value(json!({
+            "key3": {"name": "NanoDB", "versions": [1.0, 2.0]}
+        }));
+
+        let versions = Tree::at_path(
+            json!([1.0, 2.0, 3.0]),
+            vec![
+                PathStep::Key("key3".to_string()),
+                PathStep::Key("versions".to_string()),
+            ],
+        );
+        assert_eq!(versions.path_string(), "key3.versions");
+
+        tree.merge_from(versions).unwrap();
+        assert_eq!(
+            tree.inner(),
+            json!({"key3": {"name": "NanoDB", "versions": [1.0, 2.0, 3.0]}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_path_steps() {
+        let tree = Tree::from_value(json!({"key3": ["a", "b"]}));
+        assert!(tree.path_steps().is_empty());
+
+        let element = tree.get("key3").unwrap().at(0).unwrap();
+        assert_eq!(
+            element.path_steps(),
+            &[PathStep::Key("key3".to_string()), PathStep::Index(0)]
+        );
+        assert!(matches!(
+            element.path_steps().last(),
+            Some(PathStep::Index(0))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_to_json_string() {
+        let value = json!({"key1": "value1", "key2": {"inner": [1, 2.5, null]}});
+        let tree = Tree::from_value(value.clone()).get("key2").unwrap();
+
+        assert_eq!(
+            tree.to_json_string().unwrap(),
+            serde_json::to_string(&value["key2"]).unwrap()
+        );
+        assert_eq!(
+            tree.to_json_string_pretty().unwrap(),
+            serde_json::to_string_pretty(&value["key2"]).unwrap()
+        );
+        assert_eq!(tree.to_json_string().unwrap(), r#"{"inner":[1,2.5,null]}"#);
+    }
+
+    #[tokio::test]
+    async fn test_merge_array_unique() {
+        let mut tree = Tree::from_value(json!([1, 2]));
+        let merged = tree.merge_array_unique([2, 3]).unwrap();
+        assert_eq!(merged.inner(), json!([1, 2, 3]));
+
+        let mut tags = Tree::from_value(json!(["a", {"b": 1}]));
+        tags.merge_array_unique(vec![json!({"b": 1}), json!("c"), json!("c"), json!("a")])
+            .unwrap();
+        assert_eq!(tags.inner(), json!(["a", {"b": 1}, "c"]));
+
+        let mut object = Tree::from_value(json!({"key": [1]}));
+        assert!(matches!(
+            object.merge_array_unique([1]),
+            Err(NanoDBError::NotAnArray(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tree_type_names() {
+        let cases = [
+            (json!(null), "null"),
+            (json!(true), "bool"),
+            (json!(1.5), "number"),
+            (json!("a"), "string"),
+            (json!([1]), "array"),
+            (json!({"a": 1}), "object"),
+        ];
+        for (value, name) in cases {
+            let tree = Tree::from_value(value);
+            assert_eq!(tree.tree_type().as_str(), name);
+            assert_eq!(tree.tree_type().to_string(), name);
+            assert_eq!(tree.type_name(), name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_into_type_mismatch() {
+        let tree = Tree::from_value(json!({"versions": [1.0, 2.0]}));
+
+        let err = tree.get("versions").unwrap().into::<String>().unwrap_err();
+        match &err {
+            NanoDBError::TypeMismatch {
+                expected,
+                actual,
+                detail,
+            } => {
+                assert_eq!(expected, std::any::type_name::<String>());
+                assert_eq!(actual, "array");
+                assert!(!detail.is_empty());
+            }
+            _ => panic!("expected a type mismatch, got {:?}", err),
+        }
+        let message = err.to_string();
+        assert!(message.contains(std::any::type_name::<String>()));
+        assert!(message.contains("array"));
+    }
+
+    #[tokio::test]
+    async fn test_take_at() {
+        let mut tree = Tree::from_value(json!({"key": ["a", {"b": 1}, "c"]}))
+            .get("key")
+            .unwrap();
+
+        let taken = tree.take_at(1).unwrap();
+        assert_eq!(taken.inner(), json!({"b": 1}));
+        assert_eq!(
+            taken.path_steps(),
+            &[PathStep::Key("key".to_string()), PathStep::Index(1)]
+        );
+        assert_eq!(tree.inner(), json!(["a", "c"]));
+
+        assert!(matches!(
+            tree.take_at(2),
+            Err(NanoDBError::IndexOutOfBounds(2))
+        ));
+        assert!(matches!(
+            Tree::from_value(json!({})).take_at(0),
+            Err(NanoDBError::NotAnArray(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_entry() {
+        let mut tree = Tree::from_value(json!({"counter": 1}));
+
+        // occupied: modified, default not inserted
+        let counter = tree
+            .entry("counter")
             .unwrap()
-            .contains(&serde_json::json!(10)));
+            .and_modify(|v| *v = (v.as_i64().unwrap() + 1).into())
+            .or_insert(0)
+            .unwrap();
+        assert_eq!(counter.inner(), json!(2));
+        assert_eq!(counter.path_string(), "counter");
 
-        // push into object -> must fail
-        let mut tree = Tree::new(value(), vec![]).get("key1").unwrap();
-        let x = tree.push(42);
-        assert!(x.is_err());
-        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnArray(_)));
+        // vacant: not modified, default inserted
+        let entry = tree.entry("other").unwrap();
+        assert!(matches!(entry, Entry::Vacant(_)));
+        assert_eq!(entry.key(), "other");
+        let other = entry
+            .and_modify(|_| panic!("vacant entries are not modified"))
+            .or_insert_with(|| vec!["a"])
+            .unwrap();
+        assert_eq!(other.inner(), json!(["a"]));
+        assert!(matches!(tree.entry("other").unwrap(), Entry::Occupied(_)));
+        assert_eq!(tree.inner(), json!({"counter": 2, "other": ["a"]}));
+
+        let mut array = Tree::from_value(json!([1]));
+        assert!(matches!(
+            array.entry("key"),
+            Err(NanoDBError::NotAnObject(_))
+        ));
     }
+
     #[tokio::test]
-    async fn test_tree_for_each() {
-        let mut tree = Tree::new(value(), vec![]).get("key3").unwrap();
-        tree.for_each(|v| {
-            *v = Value::from(v.as_i64().unwrap() + 2i64);
-        })
-        .unwrap();
-        assert_eq!(tree.inner(), json!([3, 4, 5]));
+    async fn test_diff() {
+        let before = Tree::from_value(json!({
+            "key1": "value1",
+            "key2": {"inner_key1": 1, "inner_key2": 2},
+            "key3": {"versions": [1.0, 2.0, 3.0]}
+        }));
+        let after = Tree::from_value(json!({
+            "key1": "value1",
+            "key2": {"inner_key1": 1, "inner_key3": 3},
+            "key3": {"versions": [1.0, 2.5]},
+            "key4": true
+        }));
+
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                ("key2.inner_key2".to_string(), DiffKind::Removed(json!(2))),
+                ("key2.inner_key3".to_string(), DiffKind::Added(json!(3))),
+                (
+                    "key3.versions[1]".to_string(),
+                    DiffKind::Changed {
+                        from: json!(2.0),
+                        to: json!(2.5)
+                    }
+                ),
+                (
+                    "key3.versions[2]".to_string(),
+                    DiffKind::Removed(json!(3.0))
+                ),
+                ("key4".to_string(), DiffKind::Added(json!(true))),
+            ]
+        );
+        assert!(before.diff(&before.clone()).is_empty());
+        assert_eq!(
+            Tree::from_value(json!([1])).diff(&Tree::from_value(json!({"a": 1}))),
+            vec![(
+                String::new(),
+                DiffKind::Changed {
+                    from: json!([1]),
+                    to: json!({"a": 1})
+                }
+            )]
+        );
     }
 
     #[tokio::test]
-    async fn test_tree_len() {
-        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
-        assert_eq!(tree.len().unwrap(), 3);
+    async fn test_merge_patch() {
+        let mut tree = Tree::from_value(json!({
+            "title": "Goodbye!",
+            "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"],
+            "content": "This will be unchanged"
+        }));
+
+        let patched = tree
+            .merge_patch(&json!({
+                "title": "Hello!",
+                "phoneNumber": "+01-123-456-7890",
+                "author": {"familyName": null},
+                "tags": ["example"]
+            }))
+            .unwrap();
+        assert_eq!(
+            patched.inner(),
+            json!({
+                "title": "Hello!",
+                "author": {"givenName": "John"},
+                "tags": ["example"],
+                "content": "This will be unchanged",
+                "phoneNumber": "+01-123-456-7890"
+            })
+        );
+        assert_eq!(tree, patched);
+
+        tree.merge_patch(&json!({"title": {"main": "Hi", "sub": null}}))
+            .unwrap();
+        assert_eq!(tree.get("title").unwrap().inner(), json!({"main": "Hi"}));
+
+        let mut scalar = Tree::from_value(json!({"a": 1}));
+        scalar.merge_patch(&json!(42)).unwrap();
+        assert_eq!(scalar.inner(), json!(42));
     }
 
     #[tokio::test]
-    async fn test_tree_into() {
-        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
-        let arr: Vec<i64> = tree.into().unwrap();
-        assert_eq!(arr, vec![1, 2, 3]);
+    async fn test_as_object_and_as_array() {
+        let tree = Tree::from_value(json!({"key1": "value1", "key3": [1, 2, 3]}));
 
-        // the following one must fail (type mismatch)
-        let tree = Tree::new(value(), vec![]).get("key3").unwrap();
-        let x: Result<Vec<String>, NanoDBError> = tree.into();
-        assert!(x.is_err());
-        assert!(matches!(x.unwrap_err(), NanoDBError::TypeMismatch(_)));
+        let object = tree.as_object().unwrap();
+        assert_eq!(object.len(), 2);
+        assert_eq!(object["key1"], json!("value1"));
+        assert!(tree.as_array().is_none());
+
+        let array = tree.get("key3").unwrap();
+        assert_eq!(
+            array.as_array().unwrap(),
+            &vec![json!(1), json!(2), json!(3)]
+        );
+        assert!(array.as_object().is_none());
+
+        let scalar = tree.get("key1").unwrap();
+        assert!(scalar.as_object().is_none());
+        assert!(scalar.as_array().is_none());
     }
 }
@@ -1,7 +1,10 @@
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::{error::NanoDBError, trees::tree::PathStep};
+use crate::{
+    error::NanoDBError,
+    trees::tree::{ArrayMerge, DiffKind, PathStep},
+};
 
 fn _new_path_is_valid(
     data: &Value,
@@ -53,3 +56,497 @@ fn _goto<T: for<'de> Deserialize<'de>>(data: Value, path: Vec<PathStep>) -> Resu
     });
     serde_json::from_value(data).map_err(Into::into)
 }
+
+/// Formats a list of path steps the same way `Tree::path_string` does.
+pub(crate) fn path_to_string(path: &[PathStep]) -> String {
+    path.iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// Formats a list of path steps in the dotted grammar accepted by `parse_path`, e.g. `key3.versions[0]`.
+pub(crate) fn format_path(path: &[PathStep]) -> String {
+    let mut formatted = String::new();
+    for step in path {
+        match step {
+            PathStep::Key(key) => {
+                if !formatted.is_empty() {
+                    formatted.push('.');
+                }
+                formatted.push_str(key);
+            }
+            PathStep::Index(index) => formatted.push_str(&format!("[{}]", index)),
+        }
+    }
+    formatted
+}
+
+/// Parses a dotted path such as `key3.versions[0]` into a list of path steps.
+///
+/// Segments are separated by dots, and every segment may be followed by any number of
+/// `[index]` suffixes. An empty path yields no steps (i.e. the root).
+pub(crate) fn parse_path(path: &str) -> Result<Vec<PathStep>, NanoDBError> {
+    let mut steps = Vec::new();
+    if path.is_empty() {
+        return Ok(steps);
+    }
+
+    for segment in path.split('.') {
+        let (key, mut rest) = match segment.find('[') {
+            Some(pos) => segment.split_at(pos),
+            None => (segment, ""),
+        };
+        if key.is_empty() && rest.is_empty() {
+            return Err(NanoDBError::InvalidJSONPath);
+        }
+        if !key.is_empty() {
+            steps.push(PathStep::Key(key.to_string()));
+        }
+
+        // rest is either empty or starts with '['
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or(NanoDBError::InvalidJSONPath)?;
+            let index = rest[1..end]
+                .parse::<usize>()
+                .map_err(|_| NanoDBError::InvalidJSONPath)?;
+            steps.push(PathStep::Index(index));
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(NanoDBError::InvalidJSONPath);
+            }
+        }
+    }
+    Ok(steps)
+}
+
+/// Walks `steps` starting at `data` and returns a reference to the value they point to.
+///
+/// `base` is the path of `data` itself and is only used to build error messages.
+pub(crate) fn resolve<'a>(
+    data: &'a Value,
+    base: &[PathStep],
+    steps: &[PathStep],
+) -> Result<&'a Value, NanoDBError> {
+    let mut current = data;
+    let mut path = base.to_vec();
+    for step in steps {
+        current = match (step, current) {
+            (PathStep::Key(key), Value::Object(map)) => {
+                map.get(key).ok_or_else(|| NanoDBError::PathNotFound {
+                    path: path_to_string(&path),
+                    missing: key.clone(),
+                })?
+            }
+            (PathStep::Key(key), _) => return Err(NanoDBError::NotAnObject(key.clone())),
+            (PathStep::Index(index), Value::Array(arr)) => arr
+                .get(*index)
+                .ok_or(NanoDBError::IndexOutOfBounds(*index))?,
+            (PathStep::Index(_), _) => return Err(NanoDBError::NotAnArray(path_to_string(&path))),
+        };
+        path.push(step.clone());
+    }
+    Ok(current)
+}
+
+/// Sets `value` at `steps` below `target`, creating objects and arrays along the way.
+///
+/// `Null` placeholders are turned into an object for a key step or an array for an index step. Arrays
+/// are padded with `Null` up to the index. Fails with `InvalidJSONPath` if a step does not fit an existing
+/// value (e.g. a key on an array) or if a value has already been set at `steps`.
+pub(crate) fn insert_path(
+    target: &mut Value,
+    steps: &[PathStep],
+    value: Value,
+) -> Result<(), NanoDBError> {
+    let mut current = target;
+    for step in steps {
+        if current.is_null() {
+            *current = match step {
+                PathStep::Key(_) => Value::Object(Default::default()),
+                PathStep::Index(_) => Value::Array(vec![]),
+            };
+        }
+        current = match (step, current) {
+            (PathStep::Key(key), Value::Object(map)) => {
+                map.entry(key.clone()).or_insert(Value::Null)
+            }
+            (PathStep::Index(index), Value::Array(arr)) => {
+                if arr.len() <= *index {
+                    arr.resize(*index + 1, Value::Null);
+                }
+                &mut arr[*index]
+            }
+            _ => return Err(NanoDBError::InvalidJSONPath),
+        };
+    }
+    if !current.is_null() {
+        return Err(NanoDBError::InvalidJSONPath);
+    }
+    *current = value;
+    Ok(())
+}
+
+/// Walks `steps` starting at `data` and returns a mutable reference to the value they point to.
+///
+/// Missing keys and steps that don't fit the current value (a key on an array or an index on an
+/// object) yield `InvalidJSONPath`, while indices past the end of an array yield `IndexOutOfBounds`.
+pub(crate) fn resolve_mut<'a>(
+    data: &'a mut Value,
+    steps: &[PathStep],
+) -> Result<&'a mut Value, NanoDBError> {
+    let mut current = data;
+    for step in steps {
+        current = match (step, current) {
+            (PathStep::Key(key), Value::Object(map)) => {
+                map.get_mut(key).ok_or(NanoDBError::InvalidJSONPath)?
+            }
+            (PathStep::Index(index), Value::Array(arr)) => arr
+                .get_mut(*index)
+                .ok_or(NanoDBError::IndexOutOfBounds(*index))?,
+            _ => return Err(NanoDBError::InvalidJSONPath),
+        };
+    }
+    Ok(current)
+}
+
+/// Splits a JSON Pointer (RFC 6901) such as `/key3/versions/0` into its unescaped reference tokens.
+///
+/// The empty pointer refers to the whole document and yields no tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<String>, NanoDBError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let rest = pointer
+        .strip_prefix('/')
+        .ok_or(NanoDBError::InvalidJSONPath)?;
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Parses a JSON Pointer array index token. Leading zeros and signs are not allowed.
+pub(crate) fn parse_pointer_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || (token.len() > 1 && token.starts_with('0'))
+        || !token.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Resolves JSON Pointer tokens against `data`, turning them into path steps on the way.
+///
+/// Tokens are interpreted as keys on objects and as indices on arrays. Any token that does not
+/// resolve yields `InvalidJSONPath`.
+pub(crate) fn resolve_pointer<'a>(
+    data: &'a Value,
+    tokens: &[String],
+) -> Result<(&'a Value, Vec<PathStep>), NanoDBError> {
+    let mut current = data;
+    let mut steps = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match current {
+            Value::Object(map) => {
+                current = map.get(token).ok_or(NanoDBError::InvalidJSONPath)?;
+                steps.push(PathStep::Key(token.clone()));
+            }
+            Value::Array(arr) => {
+                let index = parse_pointer_index(token).ok_or(NanoDBError::InvalidJSONPath)?;
+                current = arr.get(index).ok_or(NanoDBError::InvalidJSONPath)?;
+                steps.push(PathStep::Index(index));
+            }
+            _ => return Err(NanoDBError::InvalidJSONPath),
+        }
+    }
+    Ok((current, steps))
+}
+
+/// Applies a JSON Patch (RFC 6902), an array of operations, to `data`.
+///
+/// The operations are applied to a copy which replaces `data` only if all of them succeed.
+/// A patch that is not an array fails with op `<not an array>` and an empty path.
+pub(crate) fn apply_patch(data: &mut Value, patch: &Value) -> Result<(), NanoDBError> {
+    let operations = patch.as_array().ok_or_else(|| NanoDBError::PatchFailed {
+        op: "<not an array>".to_string(),
+        path: String::new(),
+    })?;
+    let mut patched = data.clone();
+    for operation in operations {
+        apply_patch_op(&mut patched, operation)?;
+    }
+    *data = patched;
+    Ok(())
+}
+
+/// Applies a single JSON Patch (RFC 6902) operation such as `{"op": "add", "path": "/a", "value": 1}` to `data`.
+///
+/// On failure, `data` may be partially modified (e.g. by a `move` whose target is invalid), so callers that need
+/// atomicity should apply the patch to a copy.
+pub(crate) fn apply_patch_op(data: &mut Value, operation: &Value) -> Result<(), NanoDBError> {
+    let field = |name: &str| operation.get(name).and_then(Value::as_str);
+    let op = field("op").unwrap_or_default();
+    let path = field("path").unwrap_or_default();
+    let failed = || NanoDBError::PatchFailed {
+        op: op.to_string(),
+        path: path.to_string(),
+    };
+    if field("path").is_none() {
+        return Err(failed());
+    }
+    let value = || operation.get("value").cloned().ok_or_else(failed);
+    let from = || field("from").ok_or_else(failed);
+
+    match op {
+        "add" => patch_add(data, path, value()?),
+        "remove" => patch_remove(data, path).map(|_| ()),
+        "replace" => {
+            let value = value()?;
+            data.pointer_mut(path).map(|target| *target = value)
+        }
+        "move" => {
+            let from = from()?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(failed());
+            }
+            patch_remove(data, from).and_then(|moved| patch_add(data, path, moved))
+        }
+        "copy" => {
+            let copied = data.pointer(from()?).cloned();
+            copied.and_then(|copied| patch_add(data, path, copied))
+        }
+        "test" => (data.pointer(path) == Some(&value()?)).then_some(()),
+        _ => None,
+    }
+    .ok_or_else(failed)
+}
+
+/// Splits a JSON Pointer into the pointer of its parent and its unescaped last reference token.
+fn split_pointer(pointer: &str) -> Option<(&str, String)> {
+    let (parent, last) = pointer.rsplit_once('/')?;
+    Some((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+/// Adds `value` at `pointer`, inserting into arrays (`-` appends) and inserting or replacing object members.
+fn patch_add(data: &mut Value, pointer: &str, value: Value) -> Option<()> {
+    if pointer.is_empty() {
+        *data = value;
+        return Some(());
+    }
+    let (parent, token) = split_pointer(pointer)?;
+    match data.pointer_mut(parent)? {
+        Value::Object(map) => {
+            map.insert(token, value);
+        }
+        Value::Array(arr) if token == "-" => arr.push(value),
+        Value::Array(arr) => {
+            let index = parse_pointer_index(&token).filter(|index| *index <= arr.len())?;
+            arr.insert(index, value);
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Removes and returns the value at `pointer`. The whole document cannot be removed.
+fn patch_remove(data: &mut Value, pointer: &str) -> Option<Value> {
+    let (parent, token) = split_pointer(pointer)?;
+    match data.pointer_mut(parent)? {
+        Value::Object(map) => map.remove(&token),
+        Value::Array(arr) => {
+            let index = parse_pointer_index(&token).filter(|index| *index < arr.len())?;
+            Some(arr.remove(index))
+        }
+        _ => None,
+    }
+}
+
+/// Applies a JSON Merge Patch (RFC 7386) to `target`.
+///
+/// Object patches are merged key by key, where `null` removes the key. Any other patch replaces `target`.
+pub(crate) fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let map = target.as_object_mut().unwrap();
+    for (key, value) in patch {
+        if value.is_null() {
+            map.remove(key);
+        } else {
+            merge_patch(map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Recursively merges `source` into `target`.
+///
+/// Objects are merged key by key, arrays are combined according to `arrays`, and any other
+/// combination (including mixed types) is resolved by replacing `target` with `source`.
+pub(crate) fn merge_values(target: &mut Value, source: Value, arrays: ArrayMerge) {
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value, arrays),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(source)) if arrays == ArrayMerge::Concat => {
+            target.extend(source);
+        }
+        (target, source) => *target = source,
+    }
+}
+
+/// Compares two JSON numbers. Integers are compared exactly, so values beyond 2^53 keep their order;
+/// as soon as one side is a float both are compared as `f64`.
+pub(crate) fn compare_numbers(
+    a: &serde_json::Number,
+    b: &serde_json::Number,
+) -> std::cmp::Ordering {
+    fn as_integer(n: &serde_json::Number) -> Option<i128> {
+        n.as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+    }
+
+    match (as_integer(a), as_integer(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&b.as_f64().unwrap_or_default()),
+    }
+}
+
+/// Returns the maximum nesting depth of `value`: 0 for scalars, 1 + the depth of the deepest child for containers.
+pub(crate) fn depth(value: &Value) -> usize {
+    match value {
+        Value::Array(arr) => 1 + arr.iter().map(depth).max().unwrap_or(0),
+        Value::Object(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Returns the number of nodes in `value`, counting `value` itself and every nested scalar and container.
+pub(crate) fn count_nodes(value: &Value) -> usize {
+    match value {
+        Value::Array(arr) => 1 + arr.iter().map(count_nodes).sum::<usize>(),
+        Value::Object(map) => 1 + map.values().map(count_nodes).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// Visits `value` and all of its descendants depth-first (pre-order), passing each node's path relative to `value`.
+pub(crate) fn walk<F>(value: &Value, path: &mut Vec<PathStep>, visit: &mut F)
+where
+    F: FnMut(&[PathStep], &Value),
+{
+    visit(path, value);
+    match value {
+        Value::Array(arr) => {
+            for (index, elem) in arr.iter().enumerate() {
+                path.push(PathStep::Index(index));
+                walk(elem, path, visit);
+                path.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (key, elem) in map {
+                path.push(PathStep::Key(key.clone()));
+                walk(elem, path, visit);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Like `walk`, but passes mutable references. A node is visited before its children, so the children of a
+/// rewritten node are the new ones.
+pub(crate) fn walk_mut<F>(value: &mut Value, path: &mut Vec<PathStep>, visit: &mut F)
+where
+    F: FnMut(&[PathStep], &mut Value),
+{
+    visit(path, value);
+    match value {
+        Value::Array(arr) => {
+            for (index, elem) in arr.iter_mut().enumerate() {
+                path.push(PathStep::Index(index));
+                walk_mut(elem, path, visit);
+                path.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (key, elem) in map.iter_mut() {
+                path.push(PathStep::Key(key.clone()));
+                walk_mut(elem, path, visit);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the differences between `from` and `to`, keyed by the path (in `format_path` grammar) relative to them.
+///
+/// Objects are compared key by key and arrays index by index; any other difference is reported as a change.
+pub(crate) fn diff(
+    from: &Value,
+    to: &Value,
+    path: &mut Vec<PathStep>,
+    out: &mut Vec<(String, DiffKind)>,
+) {
+    match (from, to) {
+        (Value::Object(from_map), Value::Object(to_map)) => {
+            for (key, from_value) in from_map {
+                path.push(PathStep::Key(key.clone()));
+                match to_map.get(key) {
+                    Some(to_value) => diff(from_value, to_value, path, out),
+                    None => out.push((format_path(path), DiffKind::Removed(from_value.clone()))),
+                }
+                path.pop();
+            }
+            for (key, to_value) in to_map {
+                if !from_map.contains_key(key) {
+                    path.push(PathStep::Key(key.clone()));
+                    out.push((format_path(path), DiffKind::Added(to_value.clone())));
+                    path.pop();
+                }
+            }
+        }
+        (Value::Array(from_arr), Value::Array(to_arr)) => {
+            for index in 0..from_arr.len().max(to_arr.len()) {
+                path.push(PathStep::Index(index));
+                match (from_arr.get(index), to_arr.get(index)) {
+                    (Some(from_value), Some(to_value)) => diff(from_value, to_value, path, out),
+                    (Some(from_value), None) => {
+                        out.push((format_path(path), DiffKind::Removed(from_value.clone())))
+                    }
+                    (None, Some(to_value)) => {
+                        out.push((format_path(path), DiffKind::Added(to_value.clone())))
+                    }
+                    (None, None) => {}
+                }
+                path.pop();
+            }
+        }
+        _ if from != to => out.push((
+            format_path(path),
+            DiffKind::Changed {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        )),
+        _ => {}
+    }
+}
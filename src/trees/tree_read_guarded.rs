@@ -3,7 +3,7 @@ use tokio::sync::RwLockReadGuard;
 
 use crate::error::NanoDBError;
 
-use super::tree::Tree;
+use super::tree::{Tree, TreeType};
 
 // Define the ReadGuardedTree struct
 #[derive(Debug)]
@@ -28,12 +28,134 @@ impl<'a> ReadGuardedTree<'a> {
         Ok(self)
     }
 
+    // Navigates to the value of key if it exists, returning whether it did. The guard stays put otherwise.
+    pub fn get_optional(&mut self, key: &str) -> Result<bool, NanoDBError> {
+        match self.inner.get_optional(key)? {
+            Some(tree) => {
+                self.inner = tree;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Navigates along a dotted path (e.g. "key3.versions[0]"), see Tree::get_path
+    pub fn get_path(&mut self, path: &str) -> Result<&mut Self, NanoDBError> {
+        self.inner = self.inner.get_path(path)?;
+        Ok(self)
+    }
+
     pub fn at(&mut self, index: usize) -> Result<&mut Self, NanoDBError> {
         self.inner = self.inner.clone().at(index)?;
         Ok(self)
     }
 
-    pub fn into<T: for<'de> serde::Deserialize<'de>>(&mut self) -> Result<T, serde_json::Error> {
-        serde_json::from_value(self.inner.inner())
+    // Deserializes the value the tree is currently pointing to, see Tree::into
+    pub fn into<T: for<'de> serde::Deserialize<'de>>(&mut self) -> Result<T, NanoDBError> {
+        self.inner.clone().into::<T>()
+    }
+
+    // Length of the array or object the tree is currently pointing to
+    pub fn len(&self) -> Result<usize, NanoDBError> {
+        self.inner.len()
+    }
+
+    // Whether the array or object the tree is currently pointing to is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    // Type of the value the tree is currently pointing to
+    pub fn tree_type(&self) -> TreeType {
+        self.inner.tree_type()
+    }
+
+    // Snapshot of the tree the guard is currently pointing to
+    pub fn tree(&self) -> &Tree {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadGuardedTree;
+    use crate::{error::NanoDBError, nanodb::NanoDB, trees::tree::TreeType};
+    use serde_json::json;
+
+    fn value_str() -> String {
+        r#"{
+			"key1": "value1",
+			"key2": {
+				"inner_key1": "inner_value1",
+				"inner_key2": "inner_value2"
+			},
+			"key3": [1, 2, 3],
+			"key4": []
+		}"#
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_read_guarded_len() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut read_guarded = db.read().await;
+        read_guarded.get("key3").unwrap();
+        assert_eq!(read_guarded.len().unwrap(), 3);
+        assert!(!read_guarded.is_empty());
+
+        let mut read_guarded = db.read().await;
+        read_guarded.get("key4").unwrap();
+        assert_eq!(read_guarded.len().unwrap(), 0);
+        assert!(read_guarded.is_empty());
+
+        let mut read_guarded = db.read().await;
+        read_guarded.get("key1").unwrap();
+        assert!(matches!(
+            read_guarded.len().unwrap_err(),
+            NanoDBError::LenNotDefined(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_guarded_tree() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut read_guarded = db.read().await;
+        read_guarded.get("key3").unwrap();
+        assert!(matches!(read_guarded.tree_type(), TreeType::Array));
+        assert_eq!(read_guarded.tree().inner(), json!([1, 2, 3]));
+        assert_eq!(read_guarded.tree().path_string(), "key3");
+    }
+
+    #[tokio::test]
+    async fn test_read_guarded_get_optional_and_get_path() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut read_guarded = db.read().await;
+
+        assert!(!read_guarded.get_optional("key0").unwrap());
+        assert_eq!(read_guarded.tree_type().as_str(), "object");
+        assert!(read_guarded.get_optional("key2").unwrap());
+        let inner: String = read_guarded.get("inner_key1").unwrap().into().unwrap();
+        assert_eq!(inner, "inner_value1");
+        drop(read_guarded);
+
+        let mut read_guarded = db.read().await;
+        let third: i64 = read_guarded.get_path("key3[2]").unwrap().into().unwrap();
+        assert_eq!(third, 3);
+        let mismatch = ReadGuardedTree::into::<String>(&mut read_guarded);
+        assert!(matches!(
+            mismatch.unwrap_err(),
+            NanoDBError::TypeMismatch { actual, .. } if actual == "number"
+        ));
+        drop(read_guarded);
+
+        let mut read_guarded = db.read().await;
+        assert!(matches!(
+            read_guarded.get_path("key2.inner_key0"),
+            Err(NanoDBError::PathNotFound { .. })
+        ));
+        assert!(matches!(
+            read_guarded.get_path("key3").unwrap().get_optional("key"),
+            Err(NanoDBError::NotAnObject(_))
+        ));
     }
 }
@@ -1,10 +1,17 @@
 use serde::Serialize;
 use serde_json::Value;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
 use tokio::sync::RwLockWriteGuard;
 
-use crate::error::NanoDBError;
+use crate::{error::NanoDBError, nanodb::JournalOp};
 
-use super::tree::Tree;
+use super::{
+    tree::{Tree, TreeType},
+    tree_helper,
+};
 
 /// A struct representing a write-guarded tree.
 ///
@@ -14,10 +21,14 @@ use super::tree::Tree;
 ///
 /// * `_guard` - The write lock guard. This is not directly used, but its existence ensures that the tree cannot be modified by other threads.
 /// * `inner` - The tree that is being guarded.
+/// * `dirty` - The modification flag of the owning NanoDB instance, set whenever the tree is merged back.
+/// * `journal` - The queue of journal operations of the owning NanoDB instance, if journaling is enabled.
 #[derive(Debug)]
 pub struct WriteGuardedTree<'a> {
     _guard: RwLockWriteGuard<'a, Value>,
     tree: Tree,
+    dirty: &'a AtomicBool,
+    journal: Option<&'a Mutex<Vec<JournalOp>>>,
 }
 
 impl<'a> WriteGuardedTree<'a> {
@@ -27,15 +38,24 @@ impl<'a> WriteGuardedTree<'a> {
     ///
     /// * `guard` - The write lock guard. This is not directly used, but its existence ensures that the tree cannot be modified by other threads.
     /// * `value` - The initial JSON value of the tree.
+    /// * `dirty` - The modification flag to set when the tree is merged back into the guarded value.
+    /// * `journal` - The queue to record every merge in, or `None` if merges are not journaled.
     ///
     /// # Returns
     ///
     /// * `WriteGuardedTree` - The new WriteGuardedTree instance.
-    pub(crate) fn new(guard: RwLockWriteGuard<'a, Value>, value: Value) -> Self {
+    pub(crate) fn new(
+        guard: RwLockWriteGuard<'a, Value>,
+        value: Value,
+        dirty: &'a AtomicBool,
+        journal: Option<&'a Mutex<Vec<JournalOp>>>,
+    ) -> Self {
         let tree = Tree::new(value, vec![]);
         WriteGuardedTree {
             _guard: guard,
             tree,
+            dirty,
+            journal,
         }
     }
 
@@ -77,6 +97,42 @@ impl<'a> WriteGuardedTree<'a> {
         Ok(self)
     }
 
+    /// Navigates back to the root of the guarded value, keeping the write lock.
+    ///
+    /// Since every modification is merged into the guarded value right away, the root reflects all edits made so far.
+    ///
+    /// # Returns
+    ///
+    /// * `&mut Self` - The TreeWriteGuarded instance itself, now pointing at the root. This allows for method chaining.
+    pub fn root(&mut self) -> &mut Self {
+        self.tree = Tree::new(self._guard.clone(), vec![]);
+        self
+    }
+
+    /// Navigates `levels` steps back up the current path, keeping the write lock.
+    ///
+    /// The target is re-read from the guarded value, so it reflects all edits made so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - The number of path steps to go up. `0` stays at the current value.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself, now pointing at the ancestor. This allows for method chaining.
+    /// * `Err(NanoDBError::InvalidJSONPath)` - If `levels` is greater than the length of the current path.
+    pub fn up(&mut self, levels: usize) -> Result<&mut Self, NanoDBError> {
+        let path = self.tree.path_steps();
+        let depth = path
+            .len()
+            .checked_sub(levels)
+            .ok_or(NanoDBError::InvalidJSONPath)?;
+        let steps = path[..depth].to_vec();
+        let value = tree_helper::resolve(&self._guard, &[], &steps)?.clone();
+        self.tree = Tree::new(value, steps);
+        Ok(self)
+    }
+
     /// Inserts a key-value pair into the inner JSON object of the TreeWriteGuarded instance,
     /// at the current path of the tree.
     ///
@@ -96,6 +152,114 @@ impl<'a> WriteGuardedTree<'a> {
         Ok(self)
     }
 
+    /// Adds `delta` to the integer stored at `key` in the object the tree is currently pointing to, and then
+    /// merges the result back into the guarded value.
+    ///
+    /// If `key` does not exist, it is created with the value `delta`. Since the write lock is held, the
+    /// read-modify-write is atomic with respect to other readers and writers. A stored floating-point number
+    /// is rejected rather than truncated; use [`increment_f64`](Self::increment_f64) for float counters.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the counter.
+    /// * `delta` - The amount to add. May be negative.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the increment. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnObject)` - If the tree is not pointing to an object.
+    /// * `Err(NanoDBError::TypeMismatch)` - If the value at `key` is not an integer, or the result overflows an `i64`.
+    pub fn increment(&mut self, key: &str, delta: i64) -> Result<&mut Self, NanoDBError> {
+        let current = match self.tree.get_optional(key)? {
+            Some(tree) => tree.as_i64().ok_or_else(|| NanoDBError::TypeMismatch {
+                expected: "i64".to_string(),
+                actual: tree.type_name().to_string(),
+                detail: format!("the value at '{}' is not an integer", key),
+            })?,
+            None => 0,
+        };
+        let value = current
+            .checked_add(delta)
+            .ok_or_else(|| NanoDBError::TypeMismatch {
+                expected: "i64".to_string(),
+                actual: "number".to_string(),
+                detail: format!("incrementing '{}' overflows an i64", key),
+            })?;
+        self.insert(key, value)
+    }
+
+    /// Adds `delta` to the floating-point number stored at `key` in the object the tree is currently pointing to,
+    /// and then merges the result back into the guarded value.
+    ///
+    /// If `key` does not exist, it is created with the value `delta`. A stored integer is rejected so that integer
+    /// counters are not silently turned into floats; use [`increment`](Self::increment) for those.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the counter.
+    /// * `delta` - The amount to add. May be negative.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the increment. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnObject)` - If the tree is not pointing to an object.
+    /// * `Err(NanoDBError::TypeMismatch)` - If the value at `key` is not a float, or the result is not finite.
+    pub fn increment_f64(&mut self, key: &str, delta: f64) -> Result<&mut Self, NanoDBError> {
+        let current = match self.tree.get_optional(key)? {
+            Some(tree) => match tree.inner() {
+                Value::Number(n) if n.is_f64() => n.as_f64().unwrap_or_default(),
+                _ => {
+                    return Err(NanoDBError::TypeMismatch {
+                        expected: "f64".to_string(),
+                        actual: tree.type_name().to_string(),
+                        detail: format!("the value at '{}' is not a float", key),
+                    })
+                }
+            },
+            None => 0.0,
+        };
+        let value = current + delta;
+        if !value.is_finite() {
+            return Err(NanoDBError::TypeMismatch {
+                expected: "f64".to_string(),
+                actual: "number".to_string(),
+                detail: format!("incrementing '{}' does not yield a finite number", key),
+            });
+        }
+        self.insert(key, value)
+    }
+
+    /// Navigates to the value associated with a given key, inserting a default value first if the key does not exist.
+    /// The insertion is merged into the current JSON value of the write lock guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve the value for.
+    /// * `default` - A function producing the value to insert if `key` does not exist. The value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself, now pointing at the value of `key`. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnObject)` - If the inner value of the tree is not an object.
+    pub fn get_or_insert_with<T, F>(
+        &mut self,
+        key: &str,
+        default: F,
+    ) -> Result<&mut Self, NanoDBError>
+    where
+        T: Serialize,
+        F: FnOnce() -> T,
+    {
+        // only merge if the default was inserted, so that a plain lookup does not count as a modification
+        let inserted = !self.tree.contains_key(key)?;
+        let subtree = self.tree.get_or_insert_with(key, default)?;
+        if inserted {
+            self.merge()?;
+        }
+        self.tree = subtree;
+        Ok(self)
+    }
+
     /// Removes a key-value pair from the inner JSON object of the TreeWriteGuarded instance and then merges the result into the current JSON value of the write lock guard.
     ///
     /// # Arguments
@@ -112,6 +276,44 @@ impl<'a> WriteGuardedTree<'a> {
         Ok(self)
     }
 
+    /// Removes a key-value pair from the inner JSON object of the TreeWriteGuarded instance, merges the result into the current JSON value of the write lock guard and returns the removed value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove the value for.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tree)` - A new Tree object that represents the removed value.
+    /// * `Err(NanoDBError::NotAnObject)` - If the tree is not pointing to an object.
+    /// * `Err(NanoDBError::KeyNotFound(key))` - If `key` does not exist in the JSON object.
+    pub fn take(&mut self, key: &str) -> Result<Tree, NanoDBError> {
+        let mut tree = self.tree.clone();
+        let taken = tree.take(key)?;
+        self.tree = tree;
+        self.merge()?;
+        Ok(taken)
+    }
+
+    /// Renames a key of the inner JSON object of the TreeWriteGuarded instance and then merges the result into the current JSON value of the write lock guard.
+    ///
+    /// If `to` already exists, its value is overwritten by the value of `from`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The key to rename.
+    /// * `to` - The new name of the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the renaming and merge. This allows for method chaining.
+    /// * `Err(NanoDBError)` - If there was an error during the renaming or the merge.
+    pub fn rename_key(&mut self, from: &str, to: &str) -> Result<&mut Self, NanoDBError> {
+        self.tree = self.tree.clone().rename_key(from, to)?;
+        self.merge()?;
+        Ok(self)
+    }
+
     /// Removes an element at a specific index from the array stored in the `Tree` instance of the `TreeWriteGuarded` and then merges the result into the current JSON value of the write lock guard.
     ///
     /// # Arguments
@@ -128,6 +330,41 @@ impl<'a> WriteGuardedTree<'a> {
         Ok(self)
     }
 
+    /// Removes the first element equal to the given value from the array stored in the `Tree` instance of the `TreeWriteGuarded` and then merges the result into the current JSON value of the write lock guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to remove. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The `TreeWriteGuarded` instance itself after the removal and merge. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::ElementNotFound)` - If no element of the array is equal to `value`.
+    pub fn remove_elem<T: Serialize>(&mut self, value: T) -> Result<&mut Self, NanoDBError> {
+        self.tree = self.tree.clone().remove_elem(value)?;
+        self.merge()?;
+        Ok(self)
+    }
+
+    /// Replaces the value the tree is currently pointing to, and then merges the result back into the guarded value.
+    ///
+    /// Unlike `insert`, this works for any kind of value, e.g. to overwrite an entire array.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new value. This value must implement the `Serialize` trait.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the replacement. This allows for method chaining.
+    /// * `Err(serde_json::Error)` - If there was an error serializing `value`.
+    pub fn set<T: Serialize>(&mut self, value: T) -> Result<&mut Self, NanoDBError> {
+        self.tree = self.tree.clone().set(value)?;
+        self.merge()?;
+        Ok(self)
+    }
+
     /// Pushes a value to the tree if it's currently pointing to an array.
     ///
     /// # Arguments
@@ -144,6 +381,61 @@ impl<'a> WriteGuardedTree<'a> {
         Ok(self)
     }
 
+    /// Appends multiple values to the tree if it's currently pointing to an array, and then merges the result
+    /// into the current JSON value of the write lock guard once.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - An iterator of values that implement the Serialize trait. Each value will be serialized to JSON and pushed to the array.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the values have been pushed and merged. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn extend<T, I>(&mut self, values: I) -> Result<&mut Self, NanoDBError>
+    where
+        T: Serialize,
+        I: IntoIterator<Item = T>,
+    {
+        self.tree = self.tree.clone().extend(values)?;
+        self.merge()?;
+        Ok(self)
+    }
+
+    /// Inserts a value at the given index if the tree is currently pointing to an array, and then merges the result into the current JSON value of the write lock guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index at which to insert the value.
+    /// * `value` - A value of type T that implements the Serialize trait. This value will be serialized to JSON and inserted into the array.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the insertion and merge. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    /// * `Err(NanoDBError::IndexOutOfBounds)` - If the index is greater than the length of the array.
+    pub fn insert_at<T: Serialize>(
+        &mut self,
+        index: usize,
+        value: T,
+    ) -> Result<&mut Self, NanoDBError> {
+        self.tree = self.tree.clone().insert_at(index, value)?;
+        self.merge()?;
+        Ok(self)
+    }
+
+    /// Reverses the array the tree is currently pointing to and then merges the result into the current JSON value of the write lock guard.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after reversing and merging. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn reverse(&mut self) -> Result<&mut Self, NanoDBError> {
+        self.tree = self.tree.clone().reverse()?;
+        self.merge()?;
+        Ok(self)
+    }
+
     /// Applies a function to each element of the inner array of the tree.
     ///
     /// # Arguments
@@ -163,6 +455,38 @@ impl<'a> WriteGuardedTree<'a> {
         Ok(self)
     }
 
+    /// Retains only the elements of the array the tree is currently pointing to for which the predicate returns `true`,
+    /// and then merges the result into the current JSON value of the write lock guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A mutable function that takes a reference to a `serde_json::Value` and returns whether to keep it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after filtering and merging. This allows for method chaining.
+    /// * `Err(NanoDBError::NotAnArray)` - If the inner value of the tree is not an array.
+    pub fn retain<F>(&mut self, predicate: F) -> Result<&mut Self, NanoDBError>
+    where
+        F: FnMut(&serde_json::Value) -> bool,
+    {
+        self.tree = self.tree.clone().retain(predicate)?;
+        self.merge()?;
+        Ok(self)
+    }
+
+    /// Empties the array or object the tree is currently pointing to and then merges the result into the current JSON value of the write lock guard.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after clearing and merging. This allows for method chaining.
+    /// * `Err(NanoDBError::LenNotDefined)` - If the inner value of the tree is neither an array nor an object.
+    pub fn clear(&mut self) -> Result<&mut Self, NanoDBError> {
+        self.tree = self.tree.clone().clear()?;
+        self.merge()?;
+        Ok(self)
+    }
+
     /// Converts the inner JSON object of the TreeWriteGuarded instance into a specified type.
     ///
     /// # Type Parameters
@@ -172,13 +496,16 @@ impl<'a> WriteGuardedTree<'a> {
     /// # Returns
     ///
     /// * `Ok(T)` - The JSON object converted into the specified type.
-    /// * `Err(serde_json::Error)` - If there was an error during the conversion.
-    pub fn into<T: for<'de> serde::Deserialize<'de>>(&mut self) -> Result<T, serde_json::Error> {
-        serde_json::from_value(self.tree.inner())
+    /// * `Err(NanoDBError::TypeMismatch)` - If the JSON object cannot be converted, see `Tree::into`.
+    pub fn into<T: for<'de> serde::Deserialize<'de>>(&mut self) -> Result<T, NanoDBError> {
+        self.tree.clone().into::<T>()
     }
 
     /// Merges the inner Tree (self.tree) instance into the the write lock guard.
     ///
+    /// If the owning NanoDB instance has a journal, the merge is queued without touching the disk; it is
+    /// appended to the journal ahead of the next journaled operation or by `NanoDB::flush_journal`.
+    ///
     /// # Returns
     ///
     /// * `Ok(&mut Self)` - The TreeWriteGuarded instance itself after the merge. This allows for method chaining.
@@ -190,12 +517,66 @@ impl<'a> WriteGuardedTree<'a> {
         let mut current_wrapped = Tree::new(current.clone(), vec![]);
         current_wrapped.merge_from(self.tree.clone())?;
 
+        if let Some(journal) = self.journal {
+            journal
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(JournalOp::InsertTree {
+                    path: self.tree.path(),
+                    value: self.tree.inner(),
+                });
+        }
+
         // Unwrap the value and assign it to the guard
         *current = current_wrapped.inner();
+        self.dirty.store(true, Ordering::Release);
 
         Ok(self)
     }
 
+    /// Returns the guarded value, i.e. the root of the JSON data including all changes merged so far.
+    pub(crate) fn guarded_value(&self) -> &Value {
+        &self._guard
+    }
+
+    /// Restores the guarded value to `snapshot`, discarding every change merged since it was taken.
+    ///
+    /// The tree is reset to the root of the restored value and the modification flag to `was_dirty`.
+    pub(crate) fn rollback(&mut self, snapshot: Value, was_dirty: bool) {
+        *self._guard = snapshot.clone();
+        self.tree = Tree::new(snapshot, vec![]);
+        self.dirty.store(was_dirty, Ordering::Release);
+    }
+
+    /// Returns the length of the array or object the tree is currently pointing to.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of elements or members.
+    /// * `Err(NanoDBError::LenNotDefined)` - If the inner value of the tree is neither an array nor an object.
+    pub fn len(&self) -> Result<usize, NanoDBError> {
+        self.tree.len()
+    }
+
+    /// Checks if the array or object the tree is currently pointing to is empty.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - If the inner JSON value is empty.
+    /// * `false` - If the inner JSON value is not empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the type of the value the tree is currently pointing to.
+    ///
+    /// # Returns
+    ///
+    /// * `TreeType` - The type of the inner value of the tree.
+    pub fn tree_type(&self) -> TreeType {
+        self.tree.tree_type()
+    }
+
     /// get snapshot of the tree
     pub fn tree(&self) -> &Tree {
         &self.tree
@@ -205,9 +586,11 @@ impl<'a> WriteGuardedTree<'a> {
 #[cfg(test)]
 mod tests {
 
+    use super::WriteGuardedTree;
     use crate::{
+        error::NanoDBError,
         nanodb::NanoDB,
-        trees::tree::{PathStep, Tree},
+        trees::tree::{PathStep, Tree, TreeType},
     };
     use serde_json::{json, Value};
 
@@ -242,14 +625,15 @@ mod tests {
         let value = value();
         let rwlock = tokio::sync::RwLock::new(value.clone());
         let guard = rwlock.write().await;
+        let dirty = std::sync::atomic::AtomicBool::new(false);
         let tree = Tree::new(value.clone(), vec![]);
-        let write_guarded = super::WriteGuardedTree::new(guard, value.clone());
+        let write_guarded = super::WriteGuardedTree::new(guard, value.clone(), &dirty, None);
         assert_eq!(write_guarded.tree.inner(), tree.inner());
     }
 
     #[tokio::test]
     async fn test_write_guarded_get() {
-        let db = NanoDB::new_from("/path/to/file.json", &value_str()).unwrap();
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
         let mut write_guarded = db.update().await;
 
         write_guarded.get("key2").unwrap();
@@ -265,7 +649,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_guarded_at() {
-        let db = NanoDB::new_from("/path/to/file.json", &value_str()).unwrap();
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
         let mut write_guarded = db.update().await;
         write_guarded.get("key3").unwrap().at(1).unwrap();
         let tree = Tree::new(
@@ -277,7 +661,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_guarded_insert() {
-        let db = NanoDB::new_from("/path/to/file.json", &value_str()).unwrap();
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
         let mut write_guarded = db.update().await;
         write_guarded
             .get("key2")
@@ -297,7 +681,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_guarded_remove() {
-        let db = NanoDB::new_from("/path/to/file.json", &value_str()).unwrap();
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
         let mut write_guarded = db.update().await;
         write_guarded
             .get("key2")
@@ -317,7 +701,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_guarded_remove_at() {
-        let db = NanoDB::new_from("/path/to/file.json", &value_str()).unwrap();
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
         let mut write_guarded = db.update().await;
         write_guarded.get("key3").unwrap().remove_at(1).unwrap();
         let tree = Tree::new(json!([1, 3]), vec![PathStep::Key("key3".to_string())]);
@@ -325,4 +709,375 @@ mod tests {
 
         write_guarded.release_lock();
     }
+
+    #[tokio::test]
+    async fn test_write_guarded_remove_elem() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+        write_guarded.get("key3").unwrap().remove_elem(2).unwrap();
+        let tree = Tree::new(json!([1, 3]), vec![PathStep::Key("key3".to_string())]);
+        assert_eq!(write_guarded.tree.inner(), tree.inner());
+
+        write_guarded.release_lock();
+        assert_eq!(db.data().await.get("key3").unwrap().inner(), json!([1, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_insert_at() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+        write_guarded.get("key3").unwrap().insert_at(1, 42).unwrap();
+        write_guarded.release_lock();
+        assert_eq!(
+            db.data().await.get("key3").unwrap().inner(),
+            json!([1, 42, 2, 3])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_rename_key() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+        write_guarded
+            .get("key2")
+            .unwrap()
+            .rename_key("inner_key1", "inner_key3")
+            .unwrap();
+        write_guarded.release_lock();
+        assert_eq!(
+            db.data().await.get("key2").unwrap().inner(),
+            json!({
+                "inner_key2": "inner_value2",
+                "inner_key3": "inner_value1"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_get_or_insert_with() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+        write_guarded
+            .get_or_insert_with("key4", Vec::<i64>::new)
+            .unwrap()
+            .push(1)
+            .unwrap();
+        write_guarded.release_lock();
+        assert_eq!(db.data().await.get("key4").unwrap().inner(), json!([1]));
+
+        let mut write_guarded = db.update().await;
+        write_guarded
+            .get_or_insert_with("key4", Vec::<i64>::new)
+            .unwrap()
+            .push(2)
+            .unwrap();
+        write_guarded.release_lock();
+        assert_eq!(db.data().await.get("key4").unwrap().inner(), json!([1, 2]));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_get_or_insert_with_lookup_is_not_a_modification() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("data.journal");
+        let mut db = NanoDB::new_from_temp(&value_str()).unwrap();
+        db.enable_journal(&journal);
+
+        db.update()
+            .await
+            .get_or_insert_with("key2", || json!({}))
+            .unwrap();
+        db.flush_journal().await.unwrap();
+        assert!(!journal.exists());
+        assert!(!db.write_if_dirty().await.unwrap());
+
+        db.update()
+            .await
+            .get_or_insert_with("key4", || json!({}))
+            .unwrap();
+        db.flush_journal().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&journal).unwrap().lines().count(),
+            1
+        );
+        assert!(db.write_if_dirty().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_reverse() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        db.update().await.get("key3").unwrap().reverse().unwrap();
+        assert_eq!(
+            db.data().await.get("key3").unwrap().inner(),
+            json!([3, 2, 1])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_retain() {
+        let db = NanoDB::new_from_temp(r#"{"numbers": [1, 2, 3, 4]}"#).unwrap();
+        db.update()
+            .await
+            .get("numbers")
+            .unwrap()
+            .retain(|v| v.as_i64().unwrap() % 2 == 0)
+            .unwrap();
+        assert_eq!(
+            db.data().await.get("numbers").unwrap().inner(),
+            json!([2, 4])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_extend() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        db.update()
+            .await
+            .get("key3")
+            .unwrap()
+            .extend(vec![4, 5])
+            .unwrap();
+        assert_eq!(
+            db.data().await.get("key3").unwrap().inner(),
+            json!([1, 2, 3, 4, 5])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_clear() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        db.update().await.get("key3").unwrap().clear().unwrap();
+        db.update().await.get("key2").unwrap().clear().unwrap();
+        assert_eq!(db.data().await.get("key3").unwrap().inner(), json!([]));
+        assert_eq!(db.data().await.get("key2").unwrap().inner(), json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_remove_at_persists() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        db.update().await.get("key3").unwrap().remove_at(0).unwrap();
+        assert_eq!(db.data().await.get("key3").unwrap().inner(), json!([2, 3]));
+
+        let mut write_guarded = db.update().await;
+        let x = write_guarded.get("key3").unwrap().remove_at(2);
+        assert!(matches!(x.unwrap_err(), NanoDBError::IndexOutOfBounds(2)));
+        write_guarded.release_lock();
+
+        let mut write_guarded = db.update().await;
+        let x = write_guarded.get("key2").unwrap().remove_at(0);
+        assert!(matches!(x.unwrap_err(), NanoDBError::NotAnArray(_)));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_len_and_tree_type() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+        write_guarded.get("key3").unwrap();
+        assert_eq!(write_guarded.len().unwrap(), 3);
+        assert!(!write_guarded.is_empty());
+        assert!(matches!(write_guarded.tree_type(), TreeType::Array));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_set() {
+        let db =
+            NanoDB::new_from_temp(r#"{"key3": {"name": "NanoDB", "versions": [1.0, 2.0, 3.0]}}"#)
+                .unwrap();
+        db.update()
+            .await
+            .get("key3")
+            .unwrap()
+            .get("versions")
+            .unwrap()
+            .set(vec!["4.0", "5.0"])
+            .unwrap()
+            .push("6.0")
+            .unwrap();
+        assert_eq!(
+            db.data().await.inner(),
+            json!({"key3": {"name": "NanoDB", "versions": ["4.0", "5.0", "6.0"]}})
+        );
+
+        db.update().await.set(json!({"key1": 1})).unwrap();
+        assert_eq!(db.data().await.inner(), json!({"key1": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_increment() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+
+        let mut handles = Vec::new();
+        for _ in 0..20 {
+            let db = db.clone();
+            handles.push(tokio::spawn(async move {
+                db.update().await.increment("counter", 2).unwrap();
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(db.data().await.get("counter").unwrap().inner(), json!(40));
+
+        db.update().await.increment("counter", -41).unwrap();
+        assert_eq!(db.data().await.get("counter").unwrap().inner(), json!(-1));
+
+        assert!(matches!(
+            db.update().await.increment("key1", 1).unwrap_err(),
+            NanoDBError::TypeMismatch { .. }
+        ));
+        assert!(matches!(
+            db.update()
+                .await
+                .get("key3")
+                .unwrap()
+                .increment("key", 1)
+                .unwrap_err(),
+            NanoDBError::NotAnObject(_)
+        ));
+        db.update().await.insert("max", i64::MAX).unwrap();
+        assert!(matches!(
+            db.update().await.increment("max", 1).unwrap_err(),
+            NanoDBError::TypeMismatch { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_increment_f64() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+
+        db.update().await.increment_f64("ratio", 0.25).unwrap();
+        db.update().await.increment_f64("ratio", 1.5).unwrap();
+        assert_eq!(db.data().await.get("ratio").unwrap().inner(), json!(1.75));
+
+        db.update().await.insert("float", 1.0).unwrap();
+        db.update().await.increment_f64("float", -0.5).unwrap();
+        assert_eq!(db.data().await.get("float").unwrap().inner(), json!(0.5));
+
+        assert!(matches!(
+            db.update().await.increment_f64("key2", 1.0).unwrap_err(),
+            NanoDBError::TypeMismatch { .. }
+        ));
+        assert!(matches!(
+            db.update().await.increment("float", 1).unwrap_err(),
+            NanoDBError::TypeMismatch { .. }
+        ));
+        assert!(matches!(
+            db.update()
+                .await
+                .increment_f64("float", f64::INFINITY)
+                .unwrap_err(),
+            NanoDBError::TypeMismatch { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_take() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+
+        let taken = db
+            .update()
+            .await
+            .get("key2")
+            .unwrap()
+            .take("inner_key1")
+            .unwrap();
+        assert_eq!(taken.inner(), json!("inner_value1"));
+        assert_eq!(
+            db.data().await.get("key2").unwrap().inner(),
+            json!({"inner_key2": "inner_value2"})
+        );
+        assert!(matches!(
+            db.update().await.take("key0").unwrap_err(),
+            NanoDBError::KeyNotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_root() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+
+        write_guarded
+            .get("key2")
+            .unwrap()
+            .insert("inner_key3", "inner_value3")
+            .unwrap()
+            .root()
+            .insert("key5", "value5")
+            .unwrap();
+        assert!(write_guarded.tree().is_root());
+        assert_eq!(
+            write_guarded
+                .tree()
+                .get("key2")
+                .unwrap()
+                .get("inner_key3")
+                .unwrap()
+                .inner(),
+            json!("inner_value3")
+        );
+        drop(write_guarded);
+
+        let data = db.data().await;
+        assert_eq!(data.get("key5").unwrap().inner(), json!("value5"));
+        assert_eq!(
+            data.get("key2").unwrap().get("inner_key3").unwrap().inner(),
+            json!("inner_value3")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_up() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+
+        write_guarded
+            .get("key2")
+            .unwrap()
+            .get_or_insert_with("nested", || json!({}))
+            .unwrap()
+            .insert("a", 1)
+            .unwrap()
+            .up(1)
+            .unwrap()
+            .insert("inner_key3", "inner_value3")
+            .unwrap();
+        assert_eq!(write_guarded.tree().path_string(), "key2");
+        assert_eq!(
+            write_guarded.tree().get("nested").unwrap().inner(),
+            json!({"a": 1})
+        );
+
+        assert!(matches!(
+            write_guarded.up(2).unwrap_err(),
+            NanoDBError::InvalidJSONPath
+        ));
+        assert!(write_guarded.up(1).unwrap().tree().is_root());
+        drop(write_guarded);
+
+        assert_eq!(
+            db.data().await.get("key2").unwrap().inner(),
+            json!({
+                "inner_key1": "inner_value1",
+                "inner_key2": "inner_value2",
+                "inner_key3": "inner_value3",
+                "nested": {"a": 1}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_guarded_into() {
+        let db = NanoDB::new_from_temp(&value_str()).unwrap();
+        let mut write_guarded = db.update().await;
+
+        write_guarded.get("key2").unwrap();
+        let inner: Result<String, NanoDBError> = write_guarded.get("inner_key1").unwrap().into();
+        assert_eq!(inner.unwrap(), "inner_value1");
+
+        let mismatch = WriteGuardedTree::into::<i64>(&mut write_guarded);
+        assert!(matches!(
+            mismatch.unwrap_err(),
+            NanoDBError::TypeMismatch { actual, .. } if actual == "string"
+        ));
+    }
 }
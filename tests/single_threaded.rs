@@ -50,7 +50,7 @@ async fn sync_tests() -> Result<(), NanoDBError> {
                 .get("versions")?
                 .at(0)?
                 .into::<String>(),
-            Err(NanoDBError::TypeMismatch(_))
+            Err(NanoDBError::TypeMismatch { .. })
         ),
         "Type mismatch error expected"
     );
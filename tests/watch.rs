@@ -0,0 +1,26 @@
+#![cfg(feature = "watch")]
+
+use std::time::Duration;
+
+use nanodb::{error::NanoDBError, nanodb::NanoDB};
+use serde_json::json;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn watch_reloads_on_change() -> Result<(), NanoDBError> {
+    let dir = tempdir()?;
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{"level": 1}"#)?;
+
+    let db = NanoDB::open(&path)?;
+    let mut reloads = db.watch()?;
+
+    std::fs::write(&path, r#"{"level": 2}"#)?;
+    tokio::time::timeout(Duration::from_secs(5), reloads.recv())
+        .await
+        .expect("no reload signal received")
+        .unwrap();
+    assert_eq!(db.data().await.inner(), json!({"level": 2}));
+
+    Ok(())
+}